@@ -0,0 +1,166 @@
+use crate::{
+    crypto::{CryptoBackend, Format},
+    error::FaError,
+};
+use age::{
+    secrecy::SecretString, ssh, x25519, Callbacks, Decryptor, Encryptor, Identity, IdentityFile,
+    Recipient,
+};
+use std::{
+    fs,
+    io::{BufReader, Read, Write},
+    path::PathBuf,
+    str::FromStr,
+};
+
+/// A backend that encrypts stores with age to X25519 or SSH recipients and
+/// decrypts them with an identity file (an age identity file or an ssh private
+/// key).
+#[derive(Debug, Clone)]
+pub struct Age {
+    identity_path: PathBuf,
+    recipients: Vec<String>,
+}
+
+/// Only used to prompt for the passphrase of an encrypted ssh key.
+#[derive(Debug, Clone)]
+struct PromptCallbacks;
+
+impl Callbacks for PromptCallbacks {
+    fn display_message(&self, message: &str) {
+        eprintln!("{}", message);
+    }
+
+    fn confirm(&self, message: &str, _yes_string: &str, _no_string: Option<&str>) -> Option<bool> {
+        dialoguer::Confirm::new()
+            .with_prompt(message)
+            .interact()
+            .ok()
+    }
+
+    fn request_public_string(&self, description: &str) -> Option<String> {
+        dialoguer::Input::new()
+            .with_prompt(description)
+            .interact_text()
+            .ok()
+    }
+
+    fn request_passphrase(&self, description: &str) -> Option<SecretString> {
+        dialoguer::Password::new()
+            .with_prompt(description)
+            .interact()
+            .ok()
+            .map(SecretString::from)
+    }
+}
+
+impl Age {
+    pub fn new(identity_path: PathBuf, recipients: Vec<String>) -> Self {
+        Self {
+            identity_path: identity_path,
+            recipients: recipients,
+        }
+    }
+
+    fn identities(&self) -> Result<Vec<Box<dyn Identity>>, FaError> {
+        if fs::metadata(&self.identity_path).is_err() {
+            return Err(FaError::NoAgeIdentity {
+                path: self.identity_path.clone(),
+            });
+        }
+
+        // age identity files first, then fallback to ssh private keys.
+        let contents = fs::read(&self.identity_path)?;
+        if let Ok(identity_file) = IdentityFile::from_buffer(contents.as_slice()) {
+            let identities = identity_file.into_identities()?;
+            if !identities.is_empty() {
+                return Ok(identities);
+            }
+        }
+        let filename = self.identity_path.to_str().map(String::from);
+        match ssh::Identity::from_buffer(contents.as_slice(), filename) {
+            Ok(ssh::Identity::Unsupported(_)) | Err(_) => Err(FaError::NoAgeIdentity {
+                path: self.identity_path.clone(),
+            }),
+            Ok(identity) => Ok(vec![Box::new(identity.with_callbacks(PromptCallbacks))]),
+        }
+    }
+
+    fn parse_recipient(recipient: &str) -> Result<Box<dyn Recipient + Send>, FaError> {
+        if let Ok(r) = x25519::Recipient::from_str(recipient) {
+            return Ok(Box::new(r));
+        }
+        match ssh::Recipient::from_str(recipient) {
+            Ok(r) => Ok(Box::new(r)),
+            Err(_) => Err(FaError::InvalidRecipient {
+                recipient: recipient.to_string(),
+            }),
+        }
+    }
+}
+
+impl CryptoBackend for Age {
+    fn encrypt(&self, recipients: &[String], data: &[u8]) -> Result<Vec<u8>, FaError> {
+        let recipients = recipients
+            .iter()
+            .map(|recipient| Self::parse_recipient(recipient))
+            .collect::<Result<Vec<_>, _>>()?;
+        let encryptor =
+            Encryptor::with_recipients(recipients.iter().map(|r| r.as_ref() as &dyn Recipient))?;
+
+        let mut encrypted_data = Vec::new();
+        let mut writer = encryptor.wrap_output(&mut encrypted_data)?;
+        writer.write_all(data)?;
+        writer.finish()?;
+        Ok(encrypted_data)
+    }
+
+    fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, FaError> {
+        let identities = self.identities()?;
+        let decryptor = Decryptor::new_buffered(BufReader::new(data))?;
+
+        let mut decrypted_data = Vec::new();
+        decryptor
+            .decrypt(identities.iter().map(|i| i.as_ref()))?
+            .read_to_end(&mut decrypted_data)?;
+        Ok(decrypted_data)
+    }
+
+    fn validate_identity(&self) -> Result<bool, FaError> {
+        match self.identities() {
+            // an ssh key can't tell its public key, so it needs recipients.
+            Ok(identities) => {
+                self.default_recipients()?;
+                Ok(!identities.is_empty())
+            }
+            Err(FaError::NoAgeIdentity { .. }) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn default_recipients(&self) -> Result<Vec<String>, FaError> {
+        if !self.recipients.is_empty() {
+            return Ok(self.recipients.clone());
+        }
+
+        // without configured recipients, encrypt to the identity file itself.
+        let recipients = fs::read_to_string(&self.identity_path)?
+            .lines()
+            .filter_map(|line| x25519::Identity::from_str(line.trim()).ok())
+            .map(|identity| identity.to_public().to_string())
+            .collect::<Vec<_>>();
+        match recipients.is_empty() {
+            true => Err(FaError::NoRecipients),
+            false => Ok(recipients),
+        }
+    }
+
+    fn format(&self) -> Format {
+        Format::Age
+    }
+
+    fn resolve_fingerprint(&self, _key: &str) -> Result<Option<String>, FaError> {
+        // age keys are their own fingerprint.
+        Ok(None)
+    }
+}
@@ -1,4 +1,20 @@
-use clap::{crate_authors, crate_version, Parser, Subcommand};
+use crate::{
+    clipboard::ClipboardMethod,
+    config::{InnerConfigDisplay, InnerConfigGenerator},
+    crypto::Backend,
+    generator::CharacterClass,
+    output::{Column, ListingOptions, OutputFormat},
+    store::Credential,
+};
+use clap::{crate_authors, crate_version, Args, Parser, Subcommand};
+
+/// the help of the arguments that pick a single credential.
+const TARGET_HELP: &str = "a required credential id or username/email.";
+const TARGET_LONG_HELP: &str = "a required credential id or username/email. The id can be shortened to its first few characters, as shown by 'fa list'.";
+
+/// the help of the arguments that take a filter, see 'filter.rs'.
+const FILTER_HELP: &str = "an optional filter, e.g. 'site:github.com tag:work -tag:old'.";
+const FILTER_LONG_HELP: &str = "an optional filter, e.g. 'site:github.com tag:work -tag:old user:ci*'. Conditions are <field><operator><value> where the field is 'id', 'user', 'site' or 'tag' (any of the tags) and the operator is ':' (contains, or a glob with '*' and '?'), '=' (is), '~' (a regular expression) or '/' (starts with). A bare value is looked for in the user, site and tag. Conditions next to each other must all match, 'OR' matches either side, 'NOT' or '-' negates a condition and parentheses group them.";
 
 #[derive(Parser, Debug, Clone)]
 #[command(
@@ -10,6 +26,16 @@ use clap::{crate_authors, crate_version, Parser, Subcommand};
     after_help = format!("Crafted By {} | {} | MIT", crate_authors!(), crate_version!())
 )]
 pub struct FaCli {
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value_t = OutputFormat::Table,
+        help = "how the results are printed.",
+        long_help = "how the results are printed. 'plain' and 'json' leave out any styling and print status messages to stderr. 'json' also prints errors as json objects."
+    )]
+    pub format: OutputFormat,
+
     #[command(subcommand)]
     pub command: Option<FaCommands>,
 }
@@ -26,43 +52,300 @@ pub enum FaCommands {
     List {
         #[arg(long, short, required = false, help = "an optional store name.")]
         store: Option<String>,
+
+        #[arg(
+            long,
+            short,
+            required = false,
+            help = FILTER_HELP,
+            long_help = FILTER_LONG_HELP
+        )]
+        filter: Option<String>,
+
+        #[command(flatten)]
+        listing: ListingArgs,
+    },
+
+    #[command(
+        about = "show every field of a credential.",
+        long_about = "show every field of a credential, including its notes, custom fields and when it was created, modified and had its password changed. The password and secret fields are masked unless revealed."
+    )]
+    Show {
+        #[arg(
+            index = 1,
+            help = TARGET_HELP,
+            long_help = TARGET_LONG_HELP
+        )]
+        target: String,
+
+        #[arg(long, short, required = false, help = "an optional store name.")]
+        store: Option<String>,
+
+        #[arg(
+            long,
+            short = 'w',
+            required = false,
+            help = "an optional website to tell apart credentials of the same user."
+        )]
+        site: Option<String>,
+
+        #[arg(
+            long,
+            short,
+            help = "show the password and secret fields instead of masking them."
+        )]
+        reveal: bool,
     },
 
-    #[command(about = "add a new credential.")]
+    #[command(
+        about = "list the credentials with old passwords.",
+        long_about = "list the credentials whose password hasn't changed in the given period, oldest first. Credentials from stores that didn't record it yet are listed too, as their password age is unknown."
+    )]
+    Audit {
+        #[arg(long, short, required = false, help = "an optional store name.")]
+        store: Option<String>,
+
+        #[arg(
+            long,
+            default_value = "180d",
+            value_parser = humantime::parse_duration,
+            help = "the period a password is expected to change within, e.g. '90d' or '1y'."
+        )]
+        older_than: std::time::Duration,
+
+        #[command(flatten)]
+        listing: ListingArgs,
+    },
+
+    #[command(
+        about = "add a new credential.",
+        mut_group("GeneratorArgs", |group| group
+            .requires("generate")
+            .conflicts_with_all(["password", "password_stdin"]))
+    )]
     Add {
         #[arg(index = 1, help = "a required username/email.")]
         user: String,
 
-        #[arg(index = 2, help = "a required password.")]
-        password: String,
+        #[arg(
+            index = 2,
+            conflicts_with = "generate",
+            help = "an optional password. prefer the prompt or '--password-stdin'.",
+            long_help = "an optional password. Passing it here leaves it in your shell history and lets other users see it, so you are asked for it in a hidden prompt when it's left out."
+        )]
+        password: Option<String>,
+
+        #[arg(
+            long,
+            conflicts_with_all = ["password", "generate"],
+            help = "read the password from the first line of stdin."
+        )]
+        password_stdin: bool,
+
+        #[arg(
+            long,
+            short,
+            help = "generate a password instead.",
+            long_help = "generate a password instead. The generator options of 'fa generate' can be used to tweak it."
+        )]
+        generate: bool,
+
+        #[command(flatten)]
+        generator: GeneratorArgs,
 
         #[arg(long, short, required = false, help = "an optional store name.")]
         store: Option<String>,
 
-        #[arg(long, short, required = false, help = "an optional tag name.")]
-        tag: Option<String>,
+        #[arg(
+            long,
+            short,
+            required = false,
+            value_delimiter = ',',
+            help = "an optional tag name. can be repeated."
+        )]
+        tag: Vec<String>,
 
         #[arg(long, short = 'w', required = false, help = "an optional website.")]
         site: Option<String>,
+
+        #[command(flatten)]
+        extra: ExtraFieldArgs,
     },
 
     #[command(about = "remove an existing credential.")]
     Remove {
-        #[arg(index = 1, help = "a required username/email.")]
-        user: String,
+        #[arg(
+            index = 1,
+            help = TARGET_HELP,
+            long_help = TARGET_LONG_HELP
+        )]
+        target: String,
 
-        #[arg(index = 2, help = "a required password.")]
-        password: String,
+        #[arg(
+            index = 2,
+            required = false,
+            help = "an optional password to tell apart credentials of the same user."
+        )]
+        password: Option<String>,
 
         #[arg(long, short, required = false, help = "an optional store name.")]
         store: Option<String>,
     },
 
+    #[command(
+        about = "edit an existing credential.",
+        long_about = "edit an existing credential. Without any of the optional flags, you are asked for every field with the current value filled in."
+    )]
+    Edit {
+        #[arg(
+            index = 1,
+            help = TARGET_HELP,
+            long_help = TARGET_LONG_HELP
+        )]
+        target: String,
+
+        #[arg(long, short, required = false, help = "an optional store name.")]
+        store: Option<String>,
+
+        #[arg(
+            long,
+            short,
+            required = false,
+            help = "an optional new username/email."
+        )]
+        user: Option<String>,
+
+        #[arg(
+            long,
+            short,
+            required = false,
+            help = "an optional new password. prefer the prompt or '--password-stdin'.",
+            long_help = "an optional new password. Passing it here leaves it in your shell history and lets other users see it, so prefer '--password-stdin' or editing it interactively."
+        )]
+        password: Option<String>,
+
+        #[arg(
+            long,
+            conflicts_with = "password",
+            help = "read the new password from the first line of stdin."
+        )]
+        password_stdin: bool,
+
+        #[arg(
+            long,
+            short,
+            required = false,
+            value_delimiter = ',',
+            help = "optional new tag names, replacing the current ones. can be repeated. an empty one removes all the tags."
+        )]
+        tag: Vec<String>,
+
+        #[arg(
+            long,
+            short = 'w',
+            required = false,
+            help = "an optional new website. an empty one removes the website."
+        )]
+        site: Option<String>,
+
+        #[command(flatten)]
+        extra: ExtraFieldArgs,
+    },
+
+    #[command(
+        about = "print a single field of a credential.",
+        long_about = "print a single field of a credential, without any decoration so it can be used in scripts. It exits with 3 if no credential matches and with 4 if more than one does."
+    )]
+    Get {
+        #[arg(
+            index = 1,
+            help = TARGET_HELP,
+            long_help = TARGET_LONG_HELP
+        )]
+        target: String,
+
+        #[arg(long, short, required = false, help = "an optional store name.")]
+        store: Option<String>,
+
+        #[arg(
+            long,
+            short = 'w',
+            required = false,
+            help = "an optional website to tell apart credentials of the same user."
+        )]
+        site: Option<String>,
+
+        #[arg(
+            long,
+            short,
+            default_value = "password",
+            value_parser = CredentialField::parse,
+            help = "the field to print.",
+            long_help = "the field to print: 'user', 'password', 'site', 'tags', 'notes' or the name of a custom field."
+        )]
+        field: CredentialField,
+    },
+
+    #[command(
+        about = "copy a password to the clipboard.",
+        long_about = "copy a password to the clipboard without printing it. The clipboard is cleared again after the configured timeout, unless it holds something else by then. That can only be told with a 'paste_command', so with OSC 52 it's cleared no matter what it holds."
+    )]
+    Copy {
+        #[arg(
+            index = 1,
+            help = TARGET_HELP,
+            long_help = TARGET_LONG_HELP
+        )]
+        target: String,
+
+        #[arg(long, short, required = false, help = "an optional store name.")]
+        store: Option<String>,
+
+        #[arg(
+            long,
+            short = 'w',
+            required = false,
+            help = "an optional website to tell apart credentials of the same user."
+        )]
+        site: Option<String>,
+
+        #[arg(
+            long,
+            required = false,
+            help = "optional seconds until the clipboard is cleared. 0 never clears it. Without a 'paste_command' it's cleared even if something else was copied since."
+        )]
+        timeout: Option<u64>,
+    },
+
+    #[command(name = "clear-clipboard", hide = true)]
+    ClearClipboard {
+        #[arg(long, value_enum)]
+        method: ClipboardMethod,
+
+        #[arg(long)]
+        after: u64,
+    },
+
     #[command(about = "search through your store.")]
     Search {
-        #[arg(index = 1, help = "a required search query.")]
+        #[arg(
+            index = 1,
+            help = "a required search query.",
+            long_help = "a required search query. It is matched against the user, site and tag of every credential and the best matches are listed first. By default the characters of the query only need to appear in order, so 'gthb' finds 'github.com'."
+        )]
         query: String,
 
+        #[arg(
+            long,
+            short,
+            conflicts_with = "regex",
+            help = "only match the query as is."
+        )]
+        exact: bool,
+
+        #[arg(long, help = "match the query as a regular expression.")]
+        regex: bool,
+
         #[arg(long, short, required = false, help = "an optional store name.")]
         store: Option<String>,
 
@@ -70,14 +353,69 @@ pub enum FaCommands {
             long,
             short,
             required = false,
-            help = "an optional filter.",
-            long_help = "an optional filter that is applied onto your search query. The format is <filter>/<filter_query> where <filter> can be either 'site' or 'tag' and <filter_query> would be your filter specific query."
+            value_delimiter = ',',
+            help = "an optional tag the credentials must have. can be repeated.",
+            long_help = "an optional tag the credentials must have. can be repeated, in which case having any of them is enough unless '--all-tags' is passed."
+        )]
+        tag: Vec<String>,
+
+        #[arg(
+            long,
+            requires = "tag",
+            help = "only match credentials with all the tags."
+        )]
+        all_tags: bool,
+
+        #[arg(
+            long,
+            short,
+            conflicts_with = "store",
+            help = "search every store instead of a single one.",
+            long_help = "search every store instead of a single one. Stores that can't be opened are reported and skipped."
+        )]
+        all_stores: bool,
+
+        #[arg(
+            long,
+            short,
+            required = false,
+            help = FILTER_HELP,
+            long_help = FILTER_LONG_HELP
         )]
         filter: Option<String>,
+
+        #[command(flatten)]
+        listing: ListingArgs,
+    },
+
+    #[command(subcommand, about = "add or remove tags of a credential.")]
+    Tag(FaCommandTag),
+
+    #[command(
+        about = "list all the tags of a store with the number of credentials that have them."
+    )]
+    Tags {
+        #[arg(long, short, required = false, help = "an optional store name.")]
+        store: Option<String>,
+    },
+
+    #[command(about = "generate a password or a passphrase.")]
+    Generate {
+        #[command(flatten)]
+        generator: GeneratorArgs,
     },
 
     #[command(about = "initialize 'fa' and create a configuration.")]
     Init {
+        #[arg(
+            long,
+            short,
+            value_enum,
+            help = "an optional encryption backend.",
+            long_help = "an optional encryption backend. 'gpg' spawns the gpg binary, 'native' does the openpgp work in-process and 'age' uses an age identity or an ssh key."
+        )]
+        backend: Option<Backend>,
+
         #[arg(
             long,
             short,
@@ -86,6 +424,20 @@ pub enum FaCommands {
         )]
         key_fingerprint: Option<String>,
 
+        #[arg(
+            long,
+            help = "an age identity file or ssh private key.",
+            long_help = "an age identity file or ssh private key used to decrypt your stores. only used by the 'age' backend."
+        )]
+        age_identity: Option<String>,
+
+        #[arg(
+            long,
+            help = "an optional age or ssh public key to encrypt to.",
+            long_help = "an optional age (age1...) or ssh public key to encrypt your stores to. can be repeated. defaults to the public key of the age identity."
+        )]
+        age_recipient: Vec<String>,
+
         #[arg(long, short = 's', help = "a required store name.")]
         store: Option<String>,
 
@@ -93,6 +445,16 @@ pub enum FaCommands {
         store_path: Option<String>,
     },
 
+    #[command(about = "re-encrypt every store to a new key.")]
+    Rekey {
+        #[arg(
+            long,
+            help = "a required gpg key-id/fingerprint.",
+            long_help = "a required gpg key-id/fingerprint of the new key. every store is re-encrypted to it and verified before the configuration is switched over to it."
+        )]
+        to: String,
+    },
+
     #[command(about = "import your credentials from a csv file to a store.")]
     Import {
         #[arg(long, short, required = false, help = "an optional store name.")]
@@ -119,6 +481,100 @@ pub enum FaCommands {
             long_help = "a required csv file path. You can provide any name to the csv file. 'fa' will either overwrite or create one for you."
         )]
         csv_file: String,
+
+        #[arg(
+            long,
+            short,
+            required = false,
+            help = "an optional filter of the credentials to export, e.g. 'site:github.com tag:work -tag:old'.",
+            long_help = FILTER_LONG_HELP
+        )]
+        filter: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CredentialField {
+    User,
+    Password,
+    Site,
+    Tags,
+    Notes,
+    Custom(String),
+}
+
+impl CredentialField {
+    /// a built in field, or else a custom one.
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_lowercase().as_str() {
+            "" => Err("the field name is empty".to_string()),
+            "user" => Ok(CredentialField::User),
+            "password" => Ok(CredentialField::Password),
+            "site" => Ok(CredentialField::Site),
+            "tag" | "tags" => Ok(CredentialField::Tags),
+            "note" | "notes" => Ok(CredentialField::Notes),
+            _ => Ok(CredentialField::Custom(name.trim().to_string())),
+        }
+    }
+}
+
+/// A 'name=value' of a custom field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldAssignment {
+    pub name: String,
+    pub value: String,
+}
+
+impl FieldAssignment {
+    fn parse(assignment: &str) -> Result<Self, String> {
+        let (name, value) = assignment
+            .split_once('=')
+            .ok_or("expected 'name=value'".to_string())?;
+        match CredentialField::parse(name)? {
+            CredentialField::Custom(name) => Ok(FieldAssignment {
+                name: name,
+                value: value.to_string(),
+            }),
+            _ => Err(format!(
+                "'{}' is a built in field, pick another name",
+                name.trim()
+            )),
+        }
+    }
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum FaCommandTag {
+    #[command(about = "add a tag to a credential.")]
+    Add {
+        #[arg(
+            index = 1,
+            help = TARGET_HELP,
+            long_help = TARGET_LONG_HELP
+        )]
+        target: String,
+
+        #[arg(index = 2, help = "a required tag name.")]
+        tag: String,
+
+        #[arg(long, short, required = false, help = "an optional store name.")]
+        store: Option<String>,
+    },
+
+    #[command(about = "remove a tag from a credential.")]
+    Remove {
+        #[arg(
+            index = 1,
+            help = TARGET_HELP,
+            long_help = TARGET_LONG_HELP
+        )]
+        target: String,
+
+        #[arg(index = 2, help = "a required tag name.")]
+        tag: String,
+
+        #[arg(long, short, required = false, help = "an optional store name.")]
+        store: Option<String>,
     },
 }
 
@@ -144,4 +600,221 @@ pub enum FaCommandStore {
         #[arg(index = 1, help = "a required store name.")]
         store: String,
     },
+
+    #[command(about = "list the backups of a store.")]
+    Backups {
+        #[arg(index = 1, help = "a required store name.")]
+        store: String,
+    },
+
+    #[command(about = "replace a store with one of its backups.")]
+    Restore {
+        #[arg(index = 1, help = "a required store name.")]
+        store: String,
+
+        #[arg(
+            index = 2,
+            help = "a required backup id.",
+            long_help = "a required backup id. You can list the backups of a store with 'fa store backups <store>'."
+        )]
+        backup: String,
+    },
+
+    #[command(subcommand, about = "all store recipient commands.")]
+    Recipients(FaCommandStoreRecipients),
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum FaCommandStoreRecipients {
+    #[command(about = "list all the recipients of a store.")]
+    List {
+        #[arg(index = 1, help = "a required store name.")]
+        store: String,
+    },
+
+    #[command(about = "add a recipient to a store and re-encrypt it.")]
+    Add {
+        #[arg(index = 1, help = "a required store name.")]
+        store: String,
+
+        #[arg(
+            index = 2,
+            help = "a required gpg key-id/fingerprint.",
+            long_help = "a required gpg key-id/fingerprint, or an age/ssh public key for stores encrypted with age. the key must be present in your keyring."
+        )]
+        recipient: String,
+    },
+
+    #[command(about = "remove a recipient from a store and re-encrypt it.")]
+    Remove {
+        #[arg(index = 1, help = "a required store name.")]
+        store: String,
+
+        #[arg(index = 2, help = "a required gpg key-id/fingerprint.")]
+        recipient: String,
+    },
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct GeneratorArgs {
+    #[arg(long, short, help = "an optional password length.")]
+    length: Option<usize>,
+
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        help = "optional character classes to pick from.",
+        long_help = "optional comma separated character classes to pick from. Defaults to all of them."
+    )]
+    classes: Option<Vec<CharacterClass>>,
+
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        help = "optional character classes every password must contain."
+    )]
+    require: Option<Vec<CharacterClass>>,
+
+    #[arg(
+        long,
+        num_args = 0..=1,
+        default_missing_value = "true",
+        help = "leave out characters that look alike, such as '0' and 'O'."
+    )]
+    exclude_ambiguous: Option<bool>,
+
+    #[arg(
+        long,
+        num_args = 0..=1,
+        default_missing_value = "true",
+        help = "generate a passphrase of random words instead."
+    )]
+    passphrase: Option<bool>,
+
+    #[arg(long, help = "an optional number of words in a passphrase.")]
+    words: Option<usize>,
+
+    #[arg(
+        long,
+        help = "an optional separator between the words of a passphrase."
+    )]
+    separator: Option<String>,
+}
+
+impl GeneratorArgs {
+    /// the configured generator defaults overridden by the passed flags.
+    pub fn apply(&self, defaults: &InnerConfigGenerator) -> InnerConfigGenerator {
+        InnerConfigGenerator {
+            length: self.length.unwrap_or(defaults.length),
+            classes: self.classes.clone().unwrap_or(defaults.classes.clone()),
+            require: self.require.clone().unwrap_or(defaults.require.clone()),
+            exclude_ambiguous: self.exclude_ambiguous.unwrap_or(defaults.exclude_ambiguous),
+            passphrase: self.passphrase.unwrap_or(defaults.passphrase),
+            words: self.words.unwrap_or(defaults.words),
+            separator: self.separator.clone().unwrap_or(defaults.separator.clone()),
+        }
+    }
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ExtraFieldArgs {
+    #[arg(
+        long,
+        required = false,
+        help = "an optional free-form note. an empty one removes the note."
+    )]
+    note: Option<String>,
+
+    #[arg(
+        long,
+        required = false,
+        value_parser = FieldAssignment::parse,
+        value_name = "NAME=VALUE",
+        help = "an optional custom field. can be repeated.",
+        long_help = "an optional custom field, e.g. 'account=1234'. can be repeated. A field of the same name is replaced, an empty value removes it."
+    )]
+    field: Vec<FieldAssignment>,
+
+    #[arg(
+        long,
+        required = false,
+        value_parser = FieldAssignment::parse,
+        value_name = "NAME=VALUE",
+        help = "an optional custom field that is masked like the password. can be repeated.",
+        long_help = "an optional custom field that is masked like the password, e.g. 'recovery=abcd-efgh'. can be repeated. A field of the same name is replaced, an empty value removes it."
+    )]
+    secret_field: Vec<FieldAssignment>,
+}
+
+impl ExtraFieldArgs {
+    pub fn is_empty(&self) -> bool {
+        self.note.is_none() && self.field.is_empty() && self.secret_field.is_empty()
+    }
+
+    /// set the passed note & custom fields on the credential, in the order
+    /// they were passed.
+    pub fn apply(&self, credential: &mut Credential) {
+        if let Some(note) = &self.note {
+            credential.notes = match note.trim().is_empty() {
+                true => None,
+                false => Some(note.clone()),
+            };
+        }
+        for assignment in self.field.iter() {
+            credential.set_field(&assignment.name, &assignment.value, false);
+        }
+        for assignment in self.secret_field.iter() {
+            credential.set_field(&assignment.name, &assignment.value, true);
+        }
+    }
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ListingArgs {
+    #[arg(
+        long,
+        short,
+        num_args = 0..=1,
+        default_missing_value = "true",
+        help = "show the passwords instead of masking them."
+    )]
+    reveal: Option<bool>,
+
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        help = "optional columns to show.",
+        long_help = "optional comma separated columns to show, e.g. 'user,site,tags'. Defaults to the 'columns' of the [display] configuration."
+    )]
+    columns: Option<Vec<Column>>,
+
+    #[arg(long, value_enum, help = "an optional column to sort by.")]
+    sort: Option<Column>,
+}
+
+impl ListingArgs {
+    /// whether the columns were picked on the command line.
+    pub fn has_columns(&self) -> bool {
+        self.columns.is_some()
+    }
+
+    /// the configured display defaults overridden by the passed flags. the
+    /// default columns start with the store when listing more than one.
+    pub fn apply(&self, defaults: &InnerConfigDisplay, multiple_stores: bool) -> ListingOptions {
+        let columns = match &self.columns {
+            Some(columns) => columns.clone(),
+            None if multiple_stores && !defaults.columns.contains(&Column::Store) => {
+                [vec![Column::Store], defaults.columns.clone()].concat()
+            }
+            None => defaults.columns.clone(),
+        };
+        ListingOptions {
+            reveal_password: self.reveal.unwrap_or(defaults.reveal_passwords),
+            columns: columns,
+            sort: self.sort,
+        }
+    }
 }
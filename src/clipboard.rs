@@ -0,0 +1,157 @@
+use crate::{config::InnerConfigClipboard, error::FaError};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use console::Term;
+use serde::{Deserialize, Serialize};
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+/// The ways 'fa' can reach the clipboard.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipboardMethod {
+    /// osc 52 when attached to a terminal, the copy command otherwise.
+    #[default]
+    Auto,
+    /// an escape sequence that asks the terminal to set the clipboard. it
+    /// works over ssh as long as the terminal supports it.
+    Osc52,
+    /// the configured external commands, such as 'wl-copy' or 'xclip'.
+    Command,
+}
+
+/// put the text on the clipboard and return the method that was used.
+pub fn copy(text: &str, config: &InnerConfigClipboard) -> Result<ClipboardMethod, FaError> {
+    let method = resolve(config)?;
+    match method {
+        ClipboardMethod::Command => run_command(&config.copy_command, text)?,
+        _ => write_osc52(text)?,
+    }
+    Ok(method)
+}
+
+/// empty the clipboard again, unless it no longer holds the copied text.
+/// that can only be told with a paste command, so osc 52 always clears it.
+pub fn clear(
+    method: ClipboardMethod,
+    config: &InnerConfigClipboard,
+    copied: &str,
+) -> Result<(), FaError> {
+    if method == ClipboardMethod::Command
+        && !config.paste_command.is_empty()
+        && read_command(&config.paste_command)? != copied
+    {
+        return Ok(());
+    }
+    match method {
+        ClipboardMethod::Command => match config.clear_command.is_empty() {
+            true => run_command(&config.copy_command, ""),
+            false => run_command(&config.clear_command, ""),
+        },
+        _ => write_osc52(""),
+    }
+}
+
+/// clear the clipboard after a while from a detached 'fa', so this one can
+/// exit right away. the copied text goes to it through its stdin, never its
+/// arguments.
+pub fn clear_later(method: ClipboardMethod, timeout: u64, copied: &str) -> Result<(), FaError> {
+    let method_name = match method {
+        ClipboardMethod::Command => "command",
+        _ => "osc52",
+    };
+    let mut command = Command::new(std::env::current_exe()?);
+    command
+        .args(["clear-clipboard", "--method", method_name, "--after"])
+        .arg(timeout.to_string())
+        .stdin(Stdio::piped())
+        .stderr(Stdio::null());
+
+    // the escape sequence has to reach the same terminal as the copy did.
+    if method == ClipboardMethod::Command {
+        command.stdout(Stdio::null());
+    }
+
+    // a ctrl-c in the terminal shouldn't take the clearing down with it.
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    let mut child = command.spawn()?;
+    child
+        .stdin
+        .take()
+        .ok_or(FaError::UnexpectedNone)?
+        .write_all(copied.as_bytes())?;
+    Ok(())
+}
+
+fn resolve(config: &InnerConfigClipboard) -> Result<ClipboardMethod, FaError> {
+    match config.method {
+        ClipboardMethod::Auto => {
+            if Term::stdout().is_term() {
+                Ok(ClipboardMethod::Osc52)
+            } else if !config.copy_command.is_empty() {
+                Ok(ClipboardMethod::Command)
+            } else {
+                Err(FaError::NoClipboard)
+            }
+        }
+        ClipboardMethod::Command if config.copy_command.is_empty() => Err(FaError::NoClipboard),
+        method => Ok(method),
+    }
+}
+
+fn write_osc52(text: &str) -> Result<(), FaError> {
+    let mut stdout = std::io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", STANDARD.encode(text))?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// run the command with the text on its stdin.
+fn run_command(command: &[String], text: &str) -> Result<(), FaError> {
+    let (program, args) = command.split_first().ok_or(FaError::NoClipboard)?;
+    let failed = || FaError::ClipboardCommandError {
+        command: command.join(" "),
+    };
+
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(|_| failed())?;
+    child
+        .stdin
+        .take()
+        .ok_or(FaError::UnexpectedNone)?
+        .write_all(text.as_bytes())?;
+
+    match child.wait()?.success() {
+        true => Ok(()),
+        false => Err(failed()),
+    }
+}
+
+/// run the command and return what it printed.
+fn read_command(command: &[String]) -> Result<String, FaError> {
+    let (program, args) = command.split_first().ok_or(FaError::NoClipboard)?;
+    let failed = || FaError::ClipboardCommandError {
+        command: command.join(" "),
+    };
+
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|_| failed())?;
+    match output.status.success() {
+        true => Ok(String::from_utf8_lossy(&output.stdout).into_owned()),
+        false => Err(failed()),
+    }
+}
@@ -1,11 +1,14 @@
-use crate::error::FaError;
+use crate::{
+    clipboard::ClipboardMethod,
+    crypto::{self, Backend},
+    error::FaError,
+    generator::CharacterClass,
+    output::Column,
+    store::Store,
+};
 use path_absolutize::Absolutize;
 use serde::{Deserialize, Serialize};
-use std::{
-    fs::{self, OpenOptions},
-    io::Write,
-    path::Path,
-};
+use std::{fs, path::Path, time::Duration};
 
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -17,24 +20,134 @@ pub struct Config {
 pub struct InnerConfig {
     pub store: InnerConfigStore,
     pub security: InnerConfigSecurity,
+    #[serde(default)]
+    pub generator: InnerConfigGenerator,
+    #[serde(default)]
+    pub display: InnerConfigDisplay,
+    #[serde(default)]
+    pub clipboard: InnerConfigClipboard,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct InnerConfigStore {
     pub base_path: String,
     pub default_store: String,
+    #[serde(default = "default_lock_timeout")]
+    pub lock_timeout: u64,
+    #[serde(default = "default_backup_retention")]
+    pub backup_retention: usize,
+}
+
+/// seconds to wait for another 'fa' to release a store.
+fn default_lock_timeout() -> u64 {
+    10
+}
+
+/// backups to keep per store.
+fn default_backup_retention() -> usize {
+    10
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct InnerConfigSecurity {
+    #[serde(default)]
+    pub backend: Backend,
+    #[serde(default)]
     pub gpg_fingerprint: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trusted_signers: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keyring_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub age_identity: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub age_recipients: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct InnerConfigDisplay {
+    // show passwords in 'fa list' & 'fa search' without '--reveal'.
+    pub reveal_passwords: bool,
+    // the columns of 'fa list' & 'fa search' without '--columns'.
+    pub columns: Vec<Column>,
+}
+
+impl Default for InnerConfigDisplay {
+    fn default() -> Self {
+        Self {
+            reveal_passwords: false,
+            columns: Column::DEFAULT.to_vec(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct InnerConfigClipboard {
+    pub method: ClipboardMethod,
+    // seconds until 'fa copy' clears the clipboard again, 0 never does.
+    pub timeout: u64,
+    // e.g. ["wl-copy"] or ["xclip", "-selection", "clipboard"].
+    pub copy_command: Vec<String>,
+    // defaults to running the copy command with nothing on its stdin.
+    pub clear_command: Vec<String>,
+    // e.g. ["wl-paste", "--no-newline"]. lets the delayed clear leave the
+    // clipboard alone once something else was copied. without it, and with
+    // osc 52, the clipboard is cleared no matter what it holds by then.
+    pub paste_command: Vec<String>,
+}
+
+impl Default for InnerConfigClipboard {
+    fn default() -> Self {
+        Self {
+            method: ClipboardMethod::Auto,
+            timeout: 45,
+            copy_command: Vec::new(),
+            clear_command: Vec::new(),
+            paste_command: Vec::new(),
+        }
+    }
+}
+
+/// The defaults of the password generator. These double as the options of
+/// a single generation, once the command line flags are applied to them.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct InnerConfigGenerator {
+    pub length: usize,
+    pub classes: Vec<CharacterClass>,
+    pub require: Vec<CharacterClass>,
+    pub exclude_ambiguous: bool,
+    pub passphrase: bool,
+    pub words: usize,
+    pub separator: String,
+}
+
+impl Default for InnerConfigGenerator {
+    fn default() -> Self {
+        Self {
+            length: 20,
+            classes: vec![
+                CharacterClass::Lower,
+                CharacterClass::Upper,
+                CharacterClass::Digit,
+                CharacterClass::Symbol,
+            ],
+            require: Vec::new(),
+            exclude_ambiguous: false,
+            passphrase: false,
+            words: 6,
+            separator: "-".to_string(),
+        }
+    }
 }
 
 impl Config {
     pub fn new(
         store_base_path: String,
         store_name: String,
-        security_gpg_fingerprint: String,
+        security: InnerConfigSecurity,
     ) -> Result<Self, FaError> {
         let store_path_buf = Path::new(&store_base_path).absolutize()?;
         let store_path: String = store_path_buf
@@ -46,10 +159,13 @@ impl Config {
             store: InnerConfigStore {
                 base_path: store_path,
                 default_store: store_name,
+                lock_timeout: default_lock_timeout(),
+                backup_retention: default_backup_retention(),
             },
-            security: InnerConfigSecurity {
-                gpg_fingerprint: security_gpg_fingerprint,
-            },
+            security: security,
+            generator: InnerConfigGenerator::default(),
+            display: InnerConfigDisplay::default(),
+            clipboard: InnerConfigClipboard::default(),
         };
 
         // ensure store directory exists.
@@ -60,21 +176,48 @@ impl Config {
         fs::create_dir_all(&configuration_path)?;
         configuration_path = format!("{}/config.toml", configuration_path);
 
-        // save to disk
-        let config_str = toml::to_string(&inner_config)?;
-        let mut config_file = OpenOptions::new()
-            .read(true)
-            .write(true)
-            .append(false)
-            .create(true)
-            .truncate(true)
-            .open(&configuration_path)?;
-        config_file.write_all(config_str.as_bytes())?;
-
-        Ok(Config {
+        let config = Config {
             config_file_path: configuration_path,
             _inner: inner_config,
-        })
+        };
+        config.save()?;
+        Ok(config)
+    }
+
+    pub fn save(&self) -> Result<(), FaError> {
+        // a crash halfway through must not leave a truncated configuration.
+        let config_str = toml::to_string(&self._inner)?;
+        Store::write_atomically(
+            Path::new(&self.config_file_path),
+            config_str.as_bytes(),
+            true,
+        )
+    }
+
+    /// trust the signatures of another key. returns whether it wasn't
+    /// trusted yet.
+    pub fn trust_signer(&mut self, key: &str) -> bool {
+        let trusted_signers = &mut self._inner.security.trusted_signers;
+        if trusted_signers
+            .iter()
+            .any(|signer| crypto::is_same_key(signer, key))
+        {
+            return false;
+        }
+        trusted_signers.push(key.to_string());
+        true
+    }
+
+    /// stop trusting the signatures of a key. returns whether it was trusted.
+    pub fn distrust_signer(&mut self, key: &str) -> bool {
+        let trusted_signers = &mut self._inner.security.trusted_signers;
+        let count = trusted_signers.len();
+        trusted_signers.retain(|signer| !crypto::is_same_key(signer, key));
+        trusted_signers.len() != count
+    }
+
+    pub fn lock_timeout(&self) -> Duration {
+        Duration::from_secs(self._inner.store.lock_timeout)
     }
 
     pub fn load_from_disk() -> Result<Self, FaError> {
@@ -84,7 +227,8 @@ impl Config {
         match fs::metadata(file_path) {
             Ok(_) => {
                 let file_content = fs::read_to_string(file_path)?;
-                let inner_config = toml::from_str::<InnerConfig>(&file_content)?;
+                let mut inner_config = toml::from_str::<InnerConfig>(&file_content)?;
+                crypto::resolve_fingerprints(&mut inner_config.security)?;
 
                 Ok(Config {
                     config_file_path: file_path_string,
@@ -0,0 +1,219 @@
+use crate::{
+    age_backend::Age,
+    config::{get_base_directory, InnerConfigSecurity},
+    error::FaError,
+    gpg::Gpg,
+    native::NativePgp,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Debug, Display},
+    path::PathBuf,
+    str::FromStr,
+};
+
+/// An encryption backend used to encrypt/decrypt the stores. The store
+/// itself only ever deals with plain bytes and leaves the actual
+/// cryptography to whichever backend was configured.
+pub trait CryptoBackend: Debug {
+    /// encrypt (and sign, if the backend supports it) the given bytes to all
+    /// the given recipients.
+    fn encrypt(&self, recipients: &[String], data: &[u8]) -> Result<Vec<u8>, FaError>;
+
+    /// decrypt the given bytes.
+    fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, FaError>;
+
+    /// check whether the configured key identity is usable by the backend.
+    fn validate_identity(&self) -> Result<bool, FaError>;
+
+    /// the recipients of a store that has no recipient list of its own,
+    /// usually just the configured key.
+    fn default_recipients(&self) -> Result<Vec<String>, FaError>;
+
+    /// the format of the ciphertext produced by the backend.
+    fn format(&self) -> Format;
+
+    /// the full fingerprint of the key a fingerprint, key id or user id
+    /// refers to, if the backend knows exactly one such key.
+    fn resolve_fingerprint(&self, key: &str) -> Result<Option<String>, FaError>;
+}
+
+/// All the backends 'fa' knows about. This is the value of the
+/// `security.backend` key in the configuration.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    #[default]
+    Gpg,
+    Native,
+    Age,
+}
+
+impl Backend {
+    pub fn format(&self) -> Format {
+        match self {
+            Backend::Gpg | Backend::Native => Format::OpenPgp,
+            Backend::Age => Format::Age,
+        }
+    }
+}
+
+impl Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Backend::Gpg => write!(f, "gpg"),
+            Backend::Native => write!(f, "native"),
+            Backend::Age => write!(f, "age"),
+        }
+    }
+}
+
+/// The format of an encrypted store. Backends producing the same format can
+/// open each other's stores.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    OpenPgp,
+    Age,
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Format::OpenPgp => write!(f, "openpgp"),
+            Format::Age => write!(f, "age"),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = FaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "openpgp" => Ok(Format::OpenPgp),
+            "age" => Ok(Format::Age),
+            _ => Err(FaError::UnknownStoreFormat {
+                format: s.to_string(),
+            }),
+        }
+    }
+}
+
+/// whether a signature made by the given fingerprint can be trusted. short
+/// key ids are easy to collide, so only full fingerprints match, which is what
+/// the configured keys are resolved to when the configuration is loaded.
+pub fn is_trusted_signer(trusted_signers: &[String], fingerprint: &str) -> bool {
+    let fingerprint = normalize_key(fingerprint);
+    !fingerprint.is_empty()
+        && trusted_signers
+            .iter()
+            .any(|signer| normalize_key(signer) == fingerprint)
+}
+
+/// whether two fingerprints/key ids (or other public keys) refer to the same
+/// key, as far as can be told from them. key ids are just (case insensitive)
+/// suffixes of the fingerprint.
+pub fn is_same_key(a: &str, b: &str) -> bool {
+    let (a, b) = (normalize_key(a), normalize_key(b));
+    !a.is_empty() && !b.is_empty() && (a.ends_with(&b) || b.ends_with(&a))
+}
+
+fn normalize_key(key: &str) -> String {
+    key.trim_start_matches("0x").replace(' ', "").to_uppercase()
+}
+
+/// resolve the configured key & the trusted signers to full fingerprints.
+/// the ones the keyring doesn't know (exactly once) are kept as they are, so
+/// they don't match any signature.
+pub fn resolve_fingerprints(security: &mut InnerConfigSecurity) -> Result<(), FaError> {
+    if security.backend.format() != Format::OpenPgp {
+        return Ok(());
+    }
+    let backend = from_config(security)?;
+    if let Some(fingerprint) = backend.resolve_fingerprint(&security.gpg_fingerprint)? {
+        security.gpg_fingerprint = fingerprint;
+    }
+    for signer in security.trusted_signers.iter_mut() {
+        if let Some(fingerprint) = backend.resolve_fingerprint(signer)? {
+            *signer = fingerprint;
+        }
+    }
+    Ok(())
+}
+
+/// the configured backend.
+pub fn from_config(security: &InnerConfigSecurity) -> Result<Box<dyn CryptoBackend>, FaError> {
+    build(security.backend, security)
+}
+
+/// the configured backend if it can handle the given format, otherwise the
+/// default backend for that format.
+pub fn for_format(
+    format: Format,
+    security: &InnerConfigSecurity,
+) -> Result<Box<dyn CryptoBackend>, FaError> {
+    let backend = match (security.backend.format() == format, format) {
+        (true, _) => security.backend,
+        (false, Format::OpenPgp) => Backend::Gpg,
+        (false, Format::Age) => Backend::Age,
+    };
+    build(backend, security)
+}
+
+fn build(
+    backend: Backend,
+    security: &InnerConfigSecurity,
+) -> Result<Box<dyn CryptoBackend>, FaError> {
+    let backend: Box<dyn CryptoBackend> = match backend {
+        Backend::Gpg => Box::new(Gpg::new(
+            &security.gpg_fingerprint,
+            security.trusted_signers.clone(),
+        )),
+        Backend::Native => {
+            let keyring_path = match &security.keyring_path {
+                Some(path) => PathBuf::from(path),
+                None => PathBuf::from(format!("{}/keyring.asc", get_base_directory()?)),
+            };
+            Box::new(NativePgp::new(
+                &security.gpg_fingerprint,
+                keyring_path,
+                security.trusted_signers.clone(),
+            ))
+        }
+        Backend::Age => {
+            let identity_path = match &security.age_identity {
+                Some(path) => PathBuf::from(path),
+                None => PathBuf::from(format!("{}/identity.txt", get_base_directory()?)),
+            };
+            Box::new(Age::new(identity_path, security.age_recipients.clone()))
+        }
+    };
+    Ok(backend)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FINGERPRINT: &str = "9BF541BC16FE4F564F7AE6C42731B463736B7FA0";
+
+    #[test]
+    fn only_full_fingerprints_are_trusted() {
+        let trusted = [format!("0x{}", FINGERPRINT.to_lowercase())];
+        assert!(is_trusted_signer(&trusted, FINGERPRINT));
+        assert!(!is_trusted_signer(
+            &["2731B463736B7FA0".to_string()],
+            FINGERPRINT
+        ));
+        assert!(!is_trusted_signer(&["736B7FA0".to_string()], FINGERPRINT));
+        assert!(!is_trusted_signer(&[String::new()], ""));
+    }
+
+    #[test]
+    fn key_ids_are_the_same_key_as_their_fingerprint() {
+        assert!(is_same_key(FINGERPRINT, "2731b463736b7fa0"));
+        assert!(is_same_key("0x736B7FA0", FINGERPRINT));
+        assert!(!is_same_key(FINGERPRINT, "2731B463736B7FA1"));
+        assert!(!is_same_key(FINGERPRINT, ""));
+    }
+}
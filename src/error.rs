@@ -5,13 +5,121 @@ pub enum FaError {
     CredentialsAlreadyExists,
 
     /// new
-    #[error("The input is invalid. The format for filtering is <filter>/<filter_query> where filter can be either 'site' or 'tag'.", )]
-    UnexpectedFilter,
+    #[error("Could not find a credential matching '{}'.", target)]
+    NoMatchingCredential { target: String },
+
+    /// new
+    #[error(
+        "'{}' matches more than one credential ({}). Use one of their ids instead.",
+        target,
+        ids
+    )]
+    AmbiguousCredential { target: String, ids: String },
+
+    /// new
+    #[error("The password can't be empty.")]
+    EmptyPassword,
+
+    /// new
+    #[error("The tag can't be empty.")]
+    EmptyTag,
+
+    /// new
+    #[error("Could not generate a password since {}.", reason)]
+    InvalidGeneratorOptions { reason: String },
+
+    /// new
+    #[error("The credential has no {}.", field)]
+    MissingField { field: String },
+
+    /// new
+    #[error("Could not reach the clipboard. Run 'fa copy' in a terminal that supports OSC 52 or set 'copy_command' in the [clipboard] section of the configuration.")]
+    NoClipboard,
+
+    /// new
+    #[error("The clipboard command '{}' failed.", command)]
+    ClipboardCommandError { command: String },
+
+    /// new
+    #[error(
+        "Could not parse the filter at column {} ('{}'): {}.",
+        column,
+        token,
+        reason
+    )]
+    InvalidFilter {
+        column: usize,
+        token: String,
+        reason: String,
+    },
 
     /// new
     #[error("Could not find a key associated with the provided fingerprint. Are you sure the fingerprint/key id is correct and the ascociated key is present on the system?")]
     InvalidFingerprint { fingerprint: String },
 
+    /// new
+    #[error(
+        "Could not find a keyring at {:?}. You can export your key with 'gpg --export-secret-keys --armor <fingerprint>'.",
+        path
+    )]
+    NoKeyring { path: std::path::PathBuf },
+
+    /// new
+    #[error("Could not find an age identity at {:?}.", path)]
+    NoAgeIdentity { path: std::path::PathBuf },
+
+    /// new
+    #[error("'{}' is neither an age nor an ssh public key.", recipient)]
+    InvalidRecipient { recipient: String },
+
+    /// new
+    #[error("Could not figure out whom to encrypt the store to. If you are using an ssh key with the age backend, pass your public key with 'fa init --age-recipient' or add it to 'age_recipients' in the configuration.")]
+    NoRecipients,
+
+    /// new
+    #[error(
+        "A store needs at least one recipient. Add another recipient before removing '{}'.",
+        recipient
+    )]
+    LastRecipient { recipient: String },
+
+    /// new
+    #[error(
+        "Refusing to remove '{}' since it is your own key. You couldn't open the store anymore.",
+        recipient
+    )]
+    OwnRecipient { recipient: String },
+
+    /// new
+    #[error("'{}' is already a recipient of the store.", recipient)]
+    RecipientAlreadyExists { recipient: String },
+
+    /// new
+    #[error("'{}' is not a recipient of the store.", recipient)]
+    NoRecipient { recipient: String },
+
+    /// new
+    #[error(
+        "Only stores encrypted with OpenPGP can be rekeyed but the configured backend is '{}'.",
+        backend
+    )]
+    RekeyUnsupported { backend: String },
+
+    /// new
+    #[error(
+        "The re-encrypted {} store did not decrypt to the same credentials. No store was changed.",
+        store
+    )]
+    RekeyVerificationFailed { store: String },
+
+    /// new
+    #[error("The store was written by a newer version of 'fa' (store version {}). Please update 'fa' to open it.", version)]
+    UnsupportedStoreVersion { version: u64 },
+
+    /// new
+    #[error("The store was written in an unknown format '{}'.", format)]
+    UnknownStoreFormat { format: String },
+
     /// new
     #[error(
         "Could not find a onfiguration file at {:?}. Have you ran 'fa init'?",
@@ -19,6 +127,21 @@ pub enum FaError {
     )]
     NoConfiguration { path: std::path::PathBuf },
 
+    /// new
+    #[error("Could not find a backup '{}' of the {} store. You can list them with 'fa store backups {}'.", id, store, store)]
+    NoBackup { store: String, id: String },
+
+    /// new
+    #[error(
+        "The store at {:?} is in use by another 'fa' process. Gave up waiting after {} seconds.",
+        path,
+        timeout
+    )]
+    StoreLocked {
+        path: std::path::PathBuf,
+        timeout: u64,
+    },
+
     /// new
     #[error("Could not find a store at {:?}", path)]
     NoStore { path: std::path::PathBuf },
@@ -38,6 +161,31 @@ pub enum FaError {
     #[error("Could not decrypt data for the store.")]
     GPGDecryptionError,
 
+    /// new
+    #[error("Refusing to open the store since it was not signed by a trusted key (signed by {}). It might have been tampered with. If you trust the signer, add them with 'fa store recipients add' or add their fingerprint to 'trusted_signers' in the configuration.", signer)]
+    UntrustedSignature { signer: String },
+
+    /// result --> result
+    #[error("The OpenPGP backend returned the error \"{}\"", source)]
+    OpenPgpError {
+        #[from]
+        source: pgp::errors::Error,
+    },
+
+    /// result --> result
+    #[error("Could not encrypt data for the store. It returned \"{}\"", source)]
+    AgeEncryptionError {
+        #[from]
+        source: age::EncryptError,
+    },
+
+    /// result --> result
+    #[error("Could not decrypt data for the store. It returned \"{}\"", source)]
+    AgeDecryptionError {
+        #[from]
+        source: age::DecryptError,
+    },
+
     // result --> result
     #[error("{}", source)]
     IOError {
@@ -99,6 +247,16 @@ pub enum FaError {
         source: dialoguer::Error,
     },
 
+    /// result --> result
+    #[error(
+        "The search query is not a valid regular expression. It returned \"{}\"",
+        source
+    )]
+    InvalidRegex {
+        #[from]
+        source: regex::Error,
+    },
+
     /// result ---> result
     #[error("Could not handle the provided file. It returned \"{}\"", source)]
     CSVError {
@@ -106,3 +264,66 @@ pub enum FaError {
         source: csv::Error,
     },
 }
+
+impl FaError {
+    /// the exit code of 'fa' when it fails with this error. scripts can rely
+    /// on these to tell apart a missing credential from other failures.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            FaError::NoMatchingCredential { .. } => 3,
+            FaError::AmbiguousCredential { .. } => 4,
+            _ => 1,
+        }
+    }
+
+    /// the name of the variant, e.g. 'NoMatchingCredential'. it is what the
+    /// json output reports as the kind of an error.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            FaError::CredentialsAlreadyExists => "CredentialsAlreadyExists",
+            FaError::NoMatchingCredential { .. } => "NoMatchingCredential",
+            FaError::AmbiguousCredential { .. } => "AmbiguousCredential",
+            FaError::EmptyPassword => "EmptyPassword",
+            FaError::EmptyTag => "EmptyTag",
+            FaError::InvalidGeneratorOptions { .. } => "InvalidGeneratorOptions",
+            FaError::MissingField { .. } => "MissingField",
+            FaError::NoClipboard => "NoClipboard",
+            FaError::ClipboardCommandError { .. } => "ClipboardCommandError",
+            FaError::InvalidFilter { .. } => "InvalidFilter",
+            FaError::InvalidFingerprint { .. } => "InvalidFingerprint",
+            FaError::NoKeyring { .. } => "NoKeyring",
+            FaError::NoAgeIdentity { .. } => "NoAgeIdentity",
+            FaError::InvalidRecipient { .. } => "InvalidRecipient",
+            FaError::NoRecipients => "NoRecipients",
+            FaError::LastRecipient { .. } => "LastRecipient",
+            FaError::OwnRecipient { .. } => "OwnRecipient",
+            FaError::RecipientAlreadyExists { .. } => "RecipientAlreadyExists",
+            FaError::NoRecipient { .. } => "NoRecipient",
+            FaError::RekeyUnsupported { .. } => "RekeyUnsupported",
+            FaError::RekeyVerificationFailed { .. } => "RekeyVerificationFailed",
+            FaError::UnsupportedStoreVersion { .. } => "UnsupportedStoreVersion",
+            FaError::UnknownStoreFormat { .. } => "UnknownStoreFormat",
+            FaError::NoConfiguration { .. } => "NoConfiguration",
+            FaError::NoBackup { .. } => "NoBackup",
+            FaError::StoreLocked { .. } => "StoreLocked",
+            FaError::NoStore { .. } => "NoStore",
+            FaError::AlreadyPresent { .. } => "AlreadyPresent",
+            FaError::GPGEncryptionError => "GPGEncryptionError",
+            FaError::GPGDecryptionError => "GPGDecryptionError",
+            FaError::UntrustedSignature { .. } => "UntrustedSignature",
+            FaError::OpenPgpError { .. } => "OpenPgpError",
+            FaError::AgeEncryptionError { .. } => "AgeEncryptionError",
+            FaError::AgeDecryptionError { .. } => "AgeDecryptionError",
+            FaError::IOError { .. } => "IOError",
+            FaError::EnvironmentVariableError { .. } => "EnvironmentVariableError",
+            FaError::UnexpectedNone => "UnexpectedNone",
+            FaError::ByteVectorToString { .. } => "ByteVectorToString",
+            FaError::SerializeConfiguration { .. } => "SerializeConfiguration",
+            FaError::DeserializeConfiguration { .. } => "DeserializeConfiguration",
+            FaError::StoreDeOrSerialization { .. } => "StoreDeOrSerialization",
+            FaError::PromptError { .. } => "PromptError",
+            FaError::InvalidRegex { .. } => "InvalidRegex",
+            FaError::CSVError { .. } => "CSVError",
+        }
+    }
+}
@@ -1,17 +1,28 @@
 use crate::{
-    cli::{FaCli, FaCommandStore, FaCommands},
-    config::Config,
+    cli::{
+        CredentialField, ExtraFieldArgs, FaCli, FaCommandStore, FaCommandStoreRecipients,
+        FaCommandTag, FaCommands, GeneratorArgs, ListingArgs,
+    },
+    clipboard::{self, ClipboardMethod},
+    config::{Config, InnerConfigSecurity},
+    crypto::{self, Backend, Format},
     error::FaError,
-    gpg::Gpg,
-    store::{Credential, Store},
+    filter::Filter,
+    generator,
+    output::{self, Column, OutputFormat},
+    search::{self, Matcher, SearchMode},
+    store::{Credential, Entry, Store},
 };
-use clap::Parser;
-use dialoguer::Input;
+use chrono::{DateTime, Utc};
+use clap::{Parser, ValueEnum};
+use dialoguer::{Input, Password, Select};
 use path_absolutize::Absolutize;
 use serde::Deserialize;
+use serde_json::json;
 use std::{
-    ffi::OsStr,
-    fs::{self, File},
+    collections::{BTreeMap, BTreeSet},
+    fs::File,
+    io::{self, Read},
     path::Path,
 };
 
@@ -27,12 +38,13 @@ struct ParsedCredential {
 pub struct Fa {
     cli: FaCli,
 }
-use console::{style, Emoji};
+use console::{measure_text_width, pad_str, style, Alignment, Emoji};
 
 pub static SPARKLE: Emoji<'_, '_> = Emoji("✨ ", "");
 pub static KEY: Emoji<'_, '_> = Emoji("🔑 ", "");
 pub static MOAI: Emoji<'_, '_> = Emoji("🗿 ", "");
 pub static ROAD: Emoji<'_, '_> = Emoji("🛣️ ", "");
+pub static LOCK: Emoji<'_, '_> = Emoji("🔒 ", "");
 
 #[derive(Debug, Clone)]
 pub struct FaApplicationState {
@@ -46,18 +58,33 @@ impl Fa {
         }
     }
 
+    pub fn format(&self) -> OutputFormat {
+        self.cli.format
+    }
+
     pub fn run(&mut self) -> Result<(), FaError> {
         let cloned_command = &self.cli.command.clone();
+        self.cli.format.apply();
 
         // create/get configuration.
         let config: Config;
         if let Some(FaCommands::Init {
+            backend,
             key_fingerprint,
+            age_identity,
+            age_recipient,
             store,
             store_path,
         }) = cloned_command
         {
-            config = self.command_init(key_fingerprint, store, store_path)?;
+            config = self.command_init(
+                backend,
+                key_fingerprint,
+                age_identity,
+                age_recipient,
+                store,
+                store_path,
+            )?;
         } else {
             config = Config::load_from_disk()?;
         }
@@ -73,30 +100,122 @@ impl Fa {
 
             // command
             Some(FaCommands::Config) => self.command_config(&state),
-            Some(FaCommands::List { store }) => self.command_list(store, &state),
+            Some(FaCommands::List {
+                store,
+                filter,
+                listing,
+            }) => self.command_list(store, filter, listing, &state),
+            Some(FaCommands::Show {
+                target,
+                store,
+                site,
+                reveal,
+            }) => self.command_show(target, store, site, *reveal, &state),
+            Some(FaCommands::Audit {
+                store,
+                older_than,
+                listing,
+            }) => self.command_audit(store, *older_than, listing, &state),
             Some(FaCommands::Add {
                 user,
                 password,
+                password_stdin,
+                generate,
+                generator,
                 store,
                 site,
                 tag,
-            }) => self.command_add(user, password, store, site, tag, &state),
+                extra,
+            }) => {
+                let password =
+                    Self::new_password(password, *password_stdin, *generate, generator, &state)?;
+                self.command_add(user, &password, store, site, tag, extra, &state)
+            }
             Some(FaCommands::Remove {
+                target,
+                password,
+                store,
+            }) => {
+                if password.is_some() {
+                    Self::warn_password_argument();
+                }
+                self.command_remove(target, password, store, &state)
+            }
+            Some(FaCommands::Get {
+                target,
+                store,
+                site,
+                field,
+            }) => self.command_get(target, store, site, field, &state),
+            Some(FaCommands::Edit {
+                target,
+                store,
                 user,
                 password,
+                password_stdin,
+                tag,
+                site,
+                extra,
+            }) => {
+                let password = match (password, password_stdin) {
+                    (Some(password), _) => {
+                        Self::warn_password_argument();
+                        Some(password.clone())
+                    }
+                    (None, true) => Some(Self::read_password_stdin()?),
+                    (None, false) => None,
+                };
+                self.command_edit(target, store, user, &password, site, tag, extra, &state)
+            }
+            Some(FaCommands::Copy {
+                target,
                 store,
-            }) => self.command_remove(user, password, store, &state),
+                site,
+                timeout,
+            }) => self.command_copy(target, store, site, timeout, &state),
+            Some(FaCommands::ClearClipboard { method, after }) => {
+                self.command_clear_clipboard(*method, *after, &state)
+            }
             Some(FaCommands::Search {
                 query,
+                exact,
+                regex,
                 store,
+                tag,
+                all_tags,
+                all_stores,
                 filter,
-            }) => self.command_search(query, store, filter, &state),
+                listing,
+            }) => {
+                let mode = match (exact, regex) {
+                    (true, _) => SearchMode::Exact,
+                    (_, true) => SearchMode::Regex,
+                    _ => SearchMode::Fuzzy,
+                };
+                let tag_filter = Filter::tags(tag, *all_tags);
+                self.command_search(
+                    query,
+                    mode,
+                    store,
+                    *all_stores,
+                    filter,
+                    tag_filter,
+                    listing,
+                    &state,
+                )
+            }
+            Some(FaCommands::Tag(ft)) => self.command_group_tag(ft, &state),
+            Some(FaCommands::Tags { store }) => self.command_tags(store, &state),
+            Some(FaCommands::Generate { generator }) => self.command_generate(generator, &state),
+            Some(FaCommands::Rekey { to }) => self.command_rekey(to, &mut state),
             Some(FaCommands::Import { store, csv_file }) => {
                 self.command_import(store, csv_file, &state)
             }
-            Some(FaCommands::Export { store, csv_file }) => {
-                self.command_export(store, csv_file, &state)
-            }
+            Some(FaCommands::Export {
+                store,
+                csv_file,
+                filter,
+            }) => self.command_export(store, csv_file, filter, &state),
 
             // do nothing
             Some(FaCommands::Init { .. }) => Ok(()),
@@ -117,19 +236,18 @@ impl Fa {
         let store_file_path =
             Store::get_file_path(store_name, &state.configuration._inner.store.base_path)?;
 
+        let configuration = &state.configuration;
         match Store::check_if_exists(&store_file_path) {
-            true => Store::load(
-                store_name,
-                store_file_path,
-                &state.configuration._inner.security.gpg_fingerprint,
-            ),
+            true => Store::load(store_name, store_file_path, configuration),
             false => {
                 if create_new {
-                    Store::new(
-                        store_name,
-                        store_file_path,
-                        &state.configuration._inner.security.gpg_fingerprint,
-                    )
+                    // another 'fa' might have created it in the meantime.
+                    match Store::new(store_name, store_file_path.clone(), configuration) {
+                        Err(FaError::AlreadyPresent { .. }) => {
+                            Store::load(store_name, store_file_path, configuration)
+                        }
+                        result => result,
+                    }
                 } else {
                     Err(FaError::NoStore {
                         path: store_file_path,
@@ -139,7 +257,54 @@ impl Fa {
         }
     }
 
-    /// Command Groups
+    /// the password of a new credential. it is either passed, read from stdin,
+    /// generated or asked for in a hidden prompt.
+    fn new_password(
+        passed_password: &Option<String>,
+        password_stdin: bool,
+        generate: bool,
+        generator_args: &GeneratorArgs,
+        state: &FaApplicationState,
+    ) -> Result<String, FaError> {
+        let password = match (passed_password, password_stdin, generate) {
+            (Some(password), _, _) => {
+                Self::warn_password_argument();
+                password.clone()
+            }
+            (None, true, _) => Self::read_password_stdin()?,
+            (None, false, true) => {
+                generator::generate(&generator_args.apply(&state.configuration._inner.generator))?
+            }
+            (None, false, false) => Password::new()
+                .with_prompt(format!("{} | Password", style("fa").bold().dim()))
+                .with_confirmation(
+                    format!("{} | Repeat password", style("fa").bold().dim()),
+                    "The passwords don't match.",
+                )
+                .interact()?,
+        };
+        match password.is_empty() {
+            true => Err(FaError::EmptyPassword),
+            false => Ok(password),
+        }
+    }
+
+    /// the first line of stdin, without its line ending.
+    fn read_password_stdin() -> Result<String, FaError> {
+        let mut line = String::new();
+        io::stdin().read_line(&mut line)?;
+        Ok(line.trim_end_matches(['\n', '\r']).to_string())
+    }
+
+    fn warn_password_argument() {
+        eprintln!(
+            "{} | {} Passwords passed as arguments end up in your shell history and are visible to other users. Leave it out to be asked for it instead, or use '--password-stdin'.",
+            style("fa").bold().dim(),
+            style("Warning!").bold().yellow()
+        );
+    }
+
+    // Command Groups
 
     fn command_group_store(
         &self,
@@ -149,34 +314,42 @@ impl Fa {
         match command_store {
             FaCommandStore::List => {
                 let store_path = &state.configuration._inner.store.base_path;
-                println!(
-                    "{} | Using store directory '{}'",
-                    style("fa").bold().dim(),
-                    style(&store_path).bold().bright()
-                );
-                if fs::read_dir(store_path)?.count() == 0 {
-                    println!("{} | There are no stores yet.", style("fa").bold().dim());
-                } else {
-                    for entry in fs::read_dir(store_path)?.flatten() {
-                        if let Ok(file_type) = entry.file_type() {
-                            if file_type.is_file() {
-                                let file_name_osstring = entry.file_name();
-                                let file_name_with_extension =
-                                    file_name_osstring.to_str().ok_or(FaError::UnexpectedNone)?;
-                                let (file_name, _) = file_name_with_extension
-                                    .rsplit_once('.')
-                                    .ok_or(FaError::UnexpectedNone)?;
-                                let extension = Path::new(file_name_with_extension)
-                                    .extension()
-                                    .and_then(OsStr::to_str)
-                                    .ok_or(FaError::UnexpectedNone)?;
-
-                                if extension == "fa" {
-                                    println!("{} | {}", style("fa").bold().dim(), file_name);
-                                }
+                let store_names = Store::list(store_path)?;
+                match self.cli.format {
+                    OutputFormat::Table => {
+                        println!(
+                            "{} | Using store directory '{}'",
+                            style("fa").bold().dim(),
+                            style(&store_path).bold().bright()
+                        );
+                        if store_names.is_empty() {
+                            println!("{} | There are no stores yet.", style("fa").bold().dim());
+                        } else {
+                            for store_name in store_names.iter() {
+                                println!("{} | {}", style("fa").bold().dim(), store_name);
                             }
                         }
                     }
+                    OutputFormat::Plain => {
+                        for store_name in store_names.iter() {
+                            println!("{}", store_name);
+                        }
+                    }
+                    OutputFormat::Json => {
+                        let default_store = &state.configuration._inner.store.default_store;
+                        let mut stores = Vec::with_capacity(store_names.len());
+                        for store_name in store_names.iter() {
+                            stores.push(json!({
+                                "name": store_name,
+                                "path": Store::get_file_path(store_name, store_path)?,
+                                "default": store_name == default_store,
+                            }));
+                        }
+                        output::print_json(&json!({
+                            "base_path": store_path,
+                            "stores": stores,
+                        }))?;
+                    }
                 }
             }
             FaCommandStore::Remove { store } => {
@@ -185,13 +358,12 @@ impl Fa {
                 if !Store::check_if_exists(&store_path) {
                     return Err(FaError::NoStore { path: store_path });
                 } else {
-                    // prompt for password before decrypting.
-                    Store::load(
-                        store,
-                        store_path.clone(),
-                        &state.configuration._inner.security.gpg_fingerprint,
-                    )?;
-                    fs::remove_file(&store_path)?;
+                    // prompt for password before decrypting. the removed
+                    // store is backed up so it can be restored.
+                    let loaded_store =
+                        Store::load(store, store_path.clone(), &state.configuration)?;
+                    loaded_store.backup()?;
+                    loaded_store.delete()?;
                     println!(
                         "{} | {} removed {} store.",
                         style("fa").bold().dim(),
@@ -203,11 +375,7 @@ impl Fa {
             FaCommandStore::Add { store } => {
                 let store_path =
                     Store::get_file_path(store, &state.configuration._inner.store.base_path)?;
-                Store::new(
-                    store,
-                    store_path,
-                    &state.configuration._inner.security.gpg_fingerprint,
-                )?;
+                Store::new(store, store_path, &state.configuration)?;
                 println!(
                     "{} | {} added {} store.",
                     style("fa").bold().dim(),
@@ -221,12 +389,8 @@ impl Fa {
                     Store::get_file_path(store, &state.configuration._inner.store.base_path)?;
                 match Store::check_if_exists(&store_path) {
                     true => {
-                        let config = Config::new(
-                            state.configuration._inner.store.base_path.clone(),
-                            store.clone(),
-                            state.configuration._inner.security.gpg_fingerprint.clone(),
-                        )?;
-                        state.configuration = config;
+                        state.configuration._inner.store.default_store = store.clone();
+                        state.configuration.save()?;
                         println!(
                             "{} | {} is now your {} store.",
                             style("fa").bold().dim(),
@@ -243,18 +407,213 @@ impl Fa {
                     }
                 }
             }
+            FaCommandStore::Backups { store } => {
+                let store_path =
+                    Store::get_file_path(store, &state.configuration._inner.store.base_path)?;
+                let backup_ids = Store::list_backups(&store_path)?;
+                if backup_ids.is_empty() {
+                    println!(
+                        "{} | There are no backups of {} store yet.",
+                        style("fa").bold().dim(),
+                        style(store).bold().bright()
+                    );
+                } else {
+                    println!(
+                        "{} | Backups of {} store, newest first.",
+                        style("fa").bold().dim(),
+                        style(store).bold().bright()
+                    );
+                    for backup_id in backup_ids.iter().rev() {
+                        println!("{} | {}", style("fa").bold().dim(), backup_id);
+                    }
+                }
+            }
+            FaCommandStore::Restore { store, backup } => {
+                let store_path =
+                    Store::get_file_path(store, &state.configuration._inner.store.base_path)?;
+                let restored_store =
+                    Store::restore(store, store_path, backup, &state.configuration)?;
+                println!(
+                    "{} | {} restored {} store from backup '{}' ({} credentials).",
+                    style("fa").bold().dim(),
+                    style("Successfully").bold().green(),
+                    style(store).bright(),
+                    style(backup).bold().bright(),
+                    restored_store.data.len()
+                );
+            }
+            FaCommandStore::Recipients(fsr) => self.command_group_store_recipients(fsr, state)?,
+        }
+        Ok(())
+    }
+
+    fn command_group_store_recipients(
+        &self,
+        command_store_recipients: &FaCommandStoreRecipients,
+        state: &mut FaApplicationState,
+    ) -> Result<(), FaError> {
+        match command_store_recipients {
+            FaCommandStoreRecipients::List { store } => {
+                let store = self.get_store(&Some(store.clone()), state, false)?;
+                println!(
+                    "{} | Recipients of {} store.",
+                    style("fa").bold().dim(),
+                    style(&store.name).bold().bright()
+                );
+                for recipient in store.recipients.iter() {
+                    println!("{} | {}", style("fa").bold().dim(), recipient);
+                }
+            }
+            FaCommandStoreRecipients::Add { store, recipient } => {
+                let base_path = &state.configuration._inner.store.base_path;
+                let store_path = Store::get_file_path(store, base_path)?;
+                if !Store::check_if_exists(&store_path) {
+                    return Err(FaError::NoStore { path: store_path });
+                }
+                let is_recipient = Store::read_recipients(&store_path)?
+                    .unwrap_or_default()
+                    .iter()
+                    .any(|r| crypto::is_same_key(r, recipient));
+
+                // recipients sign the store whenever they save it, so their
+                // signatures are trusted. adding a listed recipient again only
+                // trusts it, which works before you can open a store someone
+                // else shared with you.
+                let own_fingerprint = &state.configuration._inner.security.gpg_fingerprint;
+                let is_newly_trusted = Store::read_format(&store_path)? == Format::OpenPgp
+                    && !crypto::is_same_key(own_fingerprint, recipient)
+                    && state.configuration.trust_signer(recipient);
+                if is_recipient && !is_newly_trusted {
+                    return Err(FaError::RecipientAlreadyExists {
+                        recipient: recipient.clone(),
+                    });
+                }
+
+                if !is_recipient {
+                    let mut store = self.get_store(&Some(store.clone()), state, false)?;
+                    if store
+                        .recipients
+                        .iter()
+                        .any(|r| crypto::is_same_key(r, recipient))
+                    {
+                        return Err(FaError::RecipientAlreadyExists {
+                            recipient: recipient.clone(),
+                        });
+                    }
+
+                    // re-encrypt first, the recipient list is only updated once that worked.
+                    store.recipients.push(recipient.clone());
+                    store.save()?;
+                    store.save_recipients()?;
+                    println!(
+                        "{} | {} added '{}' to the recipients of {} store.",
+                        style("fa").bold().dim(),
+                        style("Successfully").bold().green(),
+                        style(recipient).bold().bright(),
+                        style(&store.name).bold().bright()
+                    );
+                }
+                if is_newly_trusted {
+                    state.configuration.save()?;
+                    println!(
+                        "{} | Stores signed by '{}' are trusted now.",
+                        style("fa").bold().dim(),
+                        style(recipient).bold().bright()
+                    );
+                }
+            }
+            FaCommandStoreRecipients::Remove { store, recipient } => {
+                let mut store = self.get_store(&Some(store.clone()), state, false)?;
+                let recipient_index = store
+                    .recipients
+                    .iter()
+                    .position(|r| crypto::is_same_key(r, recipient))
+                    .ok_or(FaError::NoRecipient {
+                        recipient: recipient.clone(),
+                    })?;
+                if store.recipients.len() == 1 {
+                    return Err(FaError::LastRecipient {
+                        recipient: recipient.clone(),
+                    });
+                }
+                if store.is_own_recipient(&store.recipients[recipient_index])? {
+                    return Err(FaError::OwnRecipient {
+                        recipient: recipient.clone(),
+                    });
+                }
+                let removed = store.recipients.remove(recipient_index);
+
+                // re-encrypt so the removed recipient can't open new versions.
+                store.save()?;
+                store.save_recipients()?;
+                println!(
+                    "{} | {} removed '{}' from the recipients of {} store.",
+                    style("fa").bold().dim(),
+                    style("Successfully").bold().green(),
+                    style(recipient).bold().bright(),
+                    style(&store.name).bold().bright()
+                );
+
+                // its signatures are no longer trusted, unless it still shares
+                // another store.
+                if !Self::is_recipient_of_other_stores(&removed, &store.name, state)?
+                    && state.configuration.distrust_signer(&removed)
+                {
+                    state.configuration.save()?;
+                    println!(
+                        "{} | Stores signed by '{}' are not trusted anymore.",
+                        style("fa").bold().dim(),
+                        style(recipient).bold().bright()
+                    );
+                }
+            }
         }
         Ok(())
     }
 
-    /// Command
+    /// whether a key is listed as a recipient of any store but the given one.
+    fn is_recipient_of_other_stores(
+        recipient: &str,
+        store_name: &String,
+        state: &FaApplicationState,
+    ) -> Result<bool, FaError> {
+        let base_path = &state.configuration._inner.store.base_path;
+        for other_store_name in Store::list(base_path)? {
+            if &other_store_name == store_name {
+                continue;
+            }
+            let store_path = Store::get_file_path(&other_store_name, base_path)?;
+            let recipients = Store::read_recipients(&store_path)?.unwrap_or_default();
+            if recipients.iter().any(|r| crypto::is_same_key(r, recipient)) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    // Command
 
     fn command_config(&self, state: &FaApplicationState) -> Result<(), FaError> {
         let configuration_path = &state.configuration.config_file_path;
         let store_path = &state.configuration._inner.store.base_path;
         let store = &state.configuration._inner.store.default_store;
+        let backend = &state.configuration._inner.security.backend;
         let fingerprint = &state.configuration._inner.security.gpg_fingerprint;
 
+        match self.cli.format {
+            OutputFormat::Table => (),
+            OutputFormat::Plain => {
+                print!("{}", toml::to_string(&state.configuration._inner)?);
+                return Ok(());
+            }
+            OutputFormat::Json => {
+                return output::print_json(&json!({
+                    "path": configuration_path,
+                    "config": &state.configuration._inner,
+                }));
+            }
+        }
+
         let fa_header = style("fa").bold().dim();
         println!(
             "{} | Located Configuration At '{}'",
@@ -263,7 +622,20 @@ impl Fa {
         );
         println!("{} | store.path: {}", fa_header, store_path);
         println!("{} | store.default_store: {}", fa_header, store);
-        println!("{} | security.fingerprint: {} ", fa_header, fingerprint);
+        println!("{} | security.backend: {}", fa_header, backend);
+        match backend.format() {
+            Format::OpenPgp => {
+                println!("{} | security.fingerprint: {} ", fa_header, fingerprint)
+            }
+            Format::Age => {
+                let security = &state.configuration._inner.security;
+                let identity = security.age_identity.as_deref().unwrap_or("-");
+                println!("{} | security.age_identity: {}", fa_header, identity);
+                for recipient in security.age_recipients.iter() {
+                    println!("{} | security.age_recipient: {}", fa_header, recipient);
+                }
+            }
+        }
 
         Ok(())
     }
@@ -271,79 +643,156 @@ impl Fa {
     fn command_list(
         &self,
         passed_store: &Option<String>,
+        passed_filter: &Option<String>,
+        listing_args: &ListingArgs,
         state: &FaApplicationState,
     ) -> Result<(), FaError> {
+        let filter = passed_filter.as_deref().map(Filter::parse).transpose()?;
         let store = self.get_store(passed_store, state, false)?;
+        let options = listing_args.apply(&state.configuration._inner.display, false);
+        let format = self.cli.format;
 
-        println!(
-            "{} | Using store '{}'",
-            style("fa").bold().dim(),
-            style(&store.name).bold().bright()
+        output::status(
+            format,
+            format!(
+                "{} | Using store '{}'",
+                style("fa").bold().dim(),
+                style(&store.name).bold().bright()
+            ),
         );
         if store.data.is_empty() {
-            println!(
-                "{} | The store is currently empty.",
-                style("fa").bold().dim()
+            output::status(
+                format,
+                format!(
+                    "{} | The store is currently empty.",
+                    style("fa").bold().dim()
+                ),
             );
-            println!(
-                "{} | try 'fa add <login> <password>'",
-                style("fa").bold().dim()
+            output::status(
+                format,
+                format!(
+                    "{} | try 'fa add <login> <password>'",
+                    style("fa").bold().dim()
+                ),
             );
-        } else {
-            for cred in store.data.iter() {
-                let site = match &cred.site {
-                    Some(s) => s,
-                    None => "-",
-                };
+        }
+        let entries = store
+            .data
+            .iter()
+            .filter(|cred| filter.as_ref().is_none_or(|filter| filter.matches(cred)))
+            .map(|cred| Entry {
+                store: &store.name,
+                credential: cred,
+            })
+            .collect::<Vec<_>>();
+        output::print_credentials(&entries, &options, format, json!({ "store": &store.name }))
+    }
 
-                let tag = match &cred.tag {
-                    Some(t) => t,
-                    None => "-",
-                };
-                println!(
-                    "{} | {} | {} | {} | {}",
-                    style("fa").bold().dim(),
-                    cred.user,
-                    cred.password,
-                    site,
-                    tag
-                );
-            }
+    fn command_show(
+        &self,
+        target: &str,
+        passed_store: &Option<String>,
+        passed_site: &Option<String>,
+        reveal: bool,
+        state: &FaApplicationState,
+    ) -> Result<(), FaError> {
+        let store = self.get_store(passed_store, state, false)?;
+        let credential_index = store.find(target, |cred| match passed_site {
+            Some(site) => cred.site.as_ref() == Some(site),
+            None => true,
+        })?;
+        let entry = Entry {
+            store: &store.name,
+            credential: &store.data[credential_index],
+        };
+        let reveal = reveal || state.configuration._inner.display.reveal_passwords;
+        output::print_credential(&entry, reveal, self.cli.format)
+    }
+
+    fn command_audit(
+        &self,
+        passed_store: &Option<String>,
+        older_than: std::time::Duration,
+        listing_args: &ListingArgs,
+        state: &FaApplicationState,
+    ) -> Result<(), FaError> {
+        let store = self.get_store(passed_store, state, false)?;
+        let mut options = listing_args.apply(&state.configuration._inner.display, false);
+        // picked columns are shown as they are.
+        if !listing_args.has_columns() && !options.columns.contains(&Column::PasswordChanged) {
+            options.columns.push(Column::PasswordChanged);
         }
+        options.sort = options.sort.or(Some(Column::PasswordChanged));
+        let format = self.cli.format;
 
-        Ok(())
+        // a period too long to subtract is older than anything.
+        let cutoff = chrono::Duration::from_std(older_than)
+            .ok()
+            .and_then(|older_than| Utc::now().checked_sub_signed(older_than))
+            .unwrap_or(DateTime::<Utc>::MIN_UTC);
+        // an unknown password age might be any age.
+        let entries = store
+            .data
+            .iter()
+            .filter(|cred| cred.password_changed.is_none_or(|changed| changed < cutoff))
+            .map(|cred| Entry {
+                store: &store.name,
+                credential: cred,
+            })
+            .collect::<Vec<_>>();
+
+        let message = match entries.is_empty() {
+            true => "Every password has changed since",
+            false => "These passwords haven't changed since",
+        };
+        output::status(
+            format,
+            format!(
+                "{} | {} {} in {} store.",
+                style("fa").bold().dim(),
+                message,
+                style(cutoff.format("%Y-%m-%d")).bold().bright(),
+                style(&store.name).bold().bright()
+            ),
+        );
+        output::print_credentials(
+            &entries,
+            &options,
+            format,
+            json!({ "store": &store.name, "cutoff": cutoff.to_rfc3339() }),
+        )
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn command_add(
         &mut self,
         user: &str,
         password: &str,
         passed_store: &Option<String>,
         passed_site: &Option<String>,
-        passed_tag: &Option<String>,
+        passed_tags: &[String],
+        passed_extra: &ExtraFieldArgs,
         state: &FaApplicationState,
     ) -> Result<(), FaError> {
         let mut store: Store = self.get_store(passed_store, state, true)?;
+        let mut credential = Credential::new(
+            user,
+            password,
+            passed_site.to_owned(),
+            Self::tag_set(passed_tags),
+        );
+        passed_extra.apply(&mut credential);
 
         // check if exists.
-        if store
-            .data
-            .iter()
-            .any(|s| s.user.eq(user) && s.password.eq(password))
-        {
+        if store.is_duplicate(&credential) {
             return Err(FaError::CredentialsAlreadyExists);
         }
 
         // does not exist, create.
-        store.data.push(Credential {
-            password: String::from(password),
-            user: String::from(user),
-            tag: passed_tag.to_owned(),
-            site: passed_site.to_owned(),
-        });
+        store.data.push(credential);
 
         // save store.
-        store.save(&state.configuration._inner.security.gpg_fingerprint)?;
+        store.save()?;
 
         // tell user.
         println!(
@@ -358,140 +807,613 @@ impl Fa {
 
     fn command_remove(
         &mut self,
-        user: &str,
-        password: &str,
+        target: &str,
+        password: &Option<String>,
         passed_store: &Option<String>,
         state: &FaApplicationState,
     ) -> Result<(), FaError> {
-        let mut store: Store = self.get_store(passed_store, state, true)?;
-        let credential_index = store
-            .data
+        let mut store: Store = self.get_store(passed_store, state, false)?;
+        let credential_index = store.find(target, |cred| match password {
+            Some(p) => cred.password == *p,
+            None => true,
+        })?;
+
+        let credential = store.data.remove(credential_index);
+        // save store.
+        store.save()?;
+        println!(
+            "{} | You've {} removed '{}' login ({}) from {} store.",
+            style("fa").bold().dim(),
+            style("successfully").green(),
+            style(&credential.user).bold().bright(),
+            style(credential.short_id()).dim(),
+            style(&store.name).bold().bright()
+        );
+
+        Ok(())
+    }
+
+    fn command_get(
+        &self,
+        target: &str,
+        passed_store: &Option<String>,
+        passed_site: &Option<String>,
+        field: &CredentialField,
+        state: &FaApplicationState,
+    ) -> Result<(), FaError> {
+        let store = self.get_store(passed_store, state, false)?;
+        let credential_index = store.find(target, |cred| match passed_site {
+            Some(site) => cred.site.as_ref() == Some(site),
+            None => true,
+        })?;
+        let credential = &store.data[credential_index];
+
+        // the tags are printed one per line.
+        let tags = credential
+            .tags
             .iter()
-            .position(|cred| cred.user == user && cred.password == password);
+            .cloned()
+            .collect::<Vec<_>>()
+            .join("\n");
+        let (field_name, value) = match field {
+            CredentialField::User => ("user", Some(&credential.user)),
+            CredentialField::Password => ("password", Some(&credential.password)),
+            CredentialField::Site => ("site", credential.site.as_ref()),
+            CredentialField::Tags => ("tags", Some(&tags).filter(|tags| !tags.is_empty())),
+            CredentialField::Notes => ("notes", credential.notes.as_ref()),
+            CredentialField::Custom(name) => (
+                name.as_str(),
+                credential.field(name).map(|field| &field.value),
+            ),
+        };
 
-        if let Some(index) = credential_index {
-            store.data.remove(index);
-            println!(
-                "{} | You've {} removed '{}' login to {} store.",
+        // printed as is, so it can be used in scripts.
+        let value = value.ok_or(FaError::MissingField {
+            field: field_name.to_string(),
+        })?;
+        println!("{}", value);
+        Ok(())
+    }
+
+    fn command_copy(
+        &self,
+        target: &str,
+        passed_store: &Option<String>,
+        passed_site: &Option<String>,
+        passed_timeout: &Option<u64>,
+        state: &FaApplicationState,
+    ) -> Result<(), FaError> {
+        let clipboard_config = &state.configuration._inner.clipboard;
+        let timeout = passed_timeout.unwrap_or(clipboard_config.timeout);
+        let store = self.get_store(passed_store, state, false)?;
+        let credential_index = store.find(target, |cred| match passed_site {
+            Some(site) => cred.site.as_ref() == Some(site),
+            None => true,
+        })?;
+        let credential = &store.data[credential_index];
+
+        let method = clipboard::copy(&credential.password, clipboard_config)?;
+        match timeout {
+            0 => println!(
+                "{} | {} Copied the password of {} to the clipboard.",
                 style("fa").bold().dim(),
-                style("successfully").green(),
-                style(&user).bold().bright(),
-                style(&store.name).bold().bright()
-            );
-            // save store.
-            store.save(&state.configuration._inner.security.gpg_fingerprint)?;
+                KEY,
+                style(&credential.user).bold().bright()
+            ),
+            _ => {
+                clipboard::clear_later(method, timeout, &credential.password)?;
+                println!(
+                    "{} | {} Copied the password of {} to the clipboard. It's cleared in {} seconds.",
+                    style("fa").bold().dim(),
+                    KEY,
+                    style(&credential.user).bold().bright(),
+                    timeout
+                );
+            }
         }
+        Ok(())
+    }
+
+    /// run by the detached child of 'fa copy', which passes the copied
+    /// password on its stdin.
+    fn command_clear_clipboard(
+        &self,
+        method: ClipboardMethod,
+        after: u64,
+        state: &FaApplicationState,
+    ) -> Result<(), FaError> {
+        let mut copied = String::new();
+        io::stdin().read_to_string(&mut copied)?;
+        std::thread::sleep(std::time::Duration::from_secs(after));
+        clipboard::clear(method, &state.configuration._inner.clipboard, &copied)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn command_edit(
+        &mut self,
+        target: &str,
+        passed_store: &Option<String>,
+        passed_user: &Option<String>,
+        passed_password: &Option<String>,
+        passed_site: &Option<String>,
+        passed_tags: &[String],
+        passed_extra: &ExtraFieldArgs,
+        state: &FaApplicationState,
+    ) -> Result<(), FaError> {
+        let mut store: Store = self.get_store(passed_store, state, false)?;
+        let credential_index = store.find(target, |_| true)?;
+        let mut credential = store.data[credential_index].clone();
 
+        let is_interactive = passed_user.is_none()
+            && passed_password.is_none()
+            && passed_site.is_none()
+            && passed_tags.is_empty()
+            && passed_extra.is_empty();
+        match is_interactive {
+            true => Self::edit_prompt(&mut credential)?,
+            false => {
+                // an empty site removes it, an empty tag all of them.
+                let optional = |value: &String| match value.is_empty() {
+                    true => None,
+                    false => Some(value.clone()),
+                };
+                if let Some(user) = passed_user {
+                    credential.user = user.clone();
+                }
+                if let Some(password) = passed_password {
+                    credential.password = password.clone();
+                }
+                if let Some(site) = passed_site {
+                    credential.site = optional(site);
+                }
+                if !passed_tags.is_empty() {
+                    credential.tags = Self::tag_set(passed_tags);
+                }
+                passed_extra.apply(&mut credential);
+            }
+        }
+
+        // check if the edit collides with another credential.
+        if store.is_duplicate(&credential) {
+            return Err(FaError::CredentialsAlreadyExists);
+        }
+
+        credential.touch(&store.data[credential_index]);
+        store.data[credential_index] = credential;
+        store.save()?;
+
+        let credential = &store.data[credential_index];
+        println!(
+            "{} | You've {} edited '{}' login ({}) in {} store.",
+            style("fa").bold().dim(),
+            style("successfully").green(),
+            style(&credential.user).bold().bright(),
+            style(credential.short_id()).dim(),
+            style(&store.name).bold().bright()
+        );
         Ok(())
     }
 
-    fn command_search(
+    /// ask for every field of a credential with the current value filled in.
+    fn edit_prompt(credential: &mut Credential) -> Result<(), FaError> {
+        let optional = |value: String| match value.trim().is_empty() {
+            true => None,
+            false => Some(value.trim().to_string()),
+        };
+
+        credential.user = Input::new()
+            .with_prompt(format!("{} | User", style("fa").bold().dim()))
+            .with_initial_text(&credential.user)
+            .interact_text()?;
+        let password = Password::new()
+            .with_prompt(format!(
+                "{} | Password (leave empty to keep the current one)",
+                style("fa").bold().dim()
+            ))
+            .with_confirmation(
+                format!("{} | Repeat password", style("fa").bold().dim()),
+                "The passwords don't match.",
+            )
+            .allow_empty_password(true)
+            .interact()?;
+        if !password.is_empty() {
+            credential.password = password;
+        }
+        credential.site = optional(
+            Input::new()
+                .with_prompt(format!("{} | Site", style("fa").bold().dim()))
+                .with_initial_text(credential.site.clone().unwrap_or_default())
+                .allow_empty(true)
+                .interact_text()?,
+        );
+        let tags: String = Input::new()
+            .with_prompt(format!(
+                "{} | Tags (comma separated)",
+                style("fa").bold().dim()
+            ))
+            .with_initial_text(
+                credential
+                    .tags
+                    .iter()
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            )
+            .allow_empty(true)
+            .interact_text()?;
+        credential.tags = Self::tag_set(&tags.split(',').map(str::to_string).collect::<Vec<_>>());
+        credential.notes = optional(
+            Input::new()
+                .with_prompt(format!("{} | Notes", style("fa").bold().dim()))
+                .with_initial_text(credential.notes.clone().unwrap_or_default())
+                .allow_empty(true)
+                .interact_text()?,
+        );
+        Ok(())
+    }
+
+    /// the passed tags without surrounding whitespace, empty ones & duplicates.
+    fn tag_set(tags: &[String]) -> BTreeSet<String> {
+        tags.iter()
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect()
+    }
+
+    fn command_group_tag(
         &mut self,
-        passed_query: &str,
+        command_tag: &FaCommandTag,
+        state: &FaApplicationState,
+    ) -> Result<(), FaError> {
+        let (target, tag, passed_store, is_add) = match command_tag {
+            FaCommandTag::Add { target, tag, store } => (target, tag, store, true),
+            FaCommandTag::Remove { target, tag, store } => (target, tag, store, false),
+        };
+        let tag = tag.trim();
+        if tag.is_empty() {
+            return Err(FaError::EmptyTag);
+        }
+
+        let mut store: Store = self.get_store(passed_store, state, false)?;
+        let credential_index = store.find(target, |_| true)?;
+        let credential = &mut store.data[credential_index];
+        let changed = match is_add {
+            true => credential.tags.insert(tag.to_string()),
+            false => credential.tags.remove(tag),
+        };
+        let user = credential.user.clone();
+        if changed {
+            credential.modified = Some(Utc::now());
+            store.save()?;
+        }
+
+        let message = match (is_add, changed) {
+            (true, true) => "added the tag",
+            (true, false) => "already had the tag",
+            (false, true) => "removed the tag",
+            (false, false) => "never had the tag",
+        };
+        println!(
+            "{} | '{}' {} '{}' in {} store.",
+            style("fa").bold().dim(),
+            style(&user).bold().bright(),
+            match changed {
+                true => style(message).green(),
+                false => style(message).yellow(),
+            },
+            style(tag).bold(),
+            style(&store.name).bold().bright()
+        );
+        Ok(())
+    }
+
+    fn command_tags(
+        &self,
         passed_store: &Option<String>,
-        passed_filter: &Option<String>,
         state: &FaApplicationState,
     ) -> Result<(), FaError> {
         let store = self.get_store(passed_store, state, false)?;
-        let query = passed_query.to_lowercase();
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for tag in store.data.iter().flat_map(|cred| cred.tags.iter()) {
+            *counts.entry(tag).or_default() += 1;
+        }
 
-        if passed_filter.is_none() {
-            println!(
-                "{} | Searching '{}' on {} store...",
-                style("fa").bold().dim(),
-                style(&query).bold().green().bright(),
-                style(store.name).bold().bright()
-            );
-            for cred in store.data.iter() {
-                if cred.user.to_lowercase().starts_with(&query) {
-                    let site = match &cred.site {
-                        Some(s) => s,
-                        None => "-",
-                    };
-                    let tag = match &cred.tag {
-                        Some(t) => t,
-                        None => "-",
-                    };
+        match self.cli.format {
+            OutputFormat::Table => {
+                println!(
+                    "{} | Using store '{}'",
+                    style("fa").bold().dim(),
+                    style(&store.name).bold().bright()
+                );
+                if counts.is_empty() {
+                    println!("{} | There are no tags yet.", style("fa").bold().dim());
+                }
+                let width = counts.keys().map(|tag| measure_text_width(tag)).max();
+                for (tag, count) in counts.iter() {
                     println!(
-                        "{} | {} | {} | {} | {}",
+                        "{} | {} | {}",
                         style("fa").bold().dim(),
-                        cred.user,
-                        cred.password,
-                        site,
-                        tag
+                        pad_str(tag, width.unwrap_or_default(), Alignment::Left, None),
+                        count
                     );
                 }
             }
-        } else {
-            let _allowed_filters = Vec::from(["tag", "site"]);
-            let _filter = passed_filter
-                .as_ref()
-                .ok_or(FaError::UnexpectedNone)?
-                .as_str();
-
-            // verify <filter>/<filter_query> pattern.
-            let starts_with_filter = _allowed_filters
-                .iter()
-                .any(|filter| _filter.starts_with(format!("{}/", &filter).as_str()));
-            if !_filter.contains('/') || !starts_with_filter {
-                return Err(FaError::UnexpectedFilter);
+            OutputFormat::Plain => {
+                for (tag, count) in counts.iter() {
+                    println!("{}\t{}", tag, count);
+                }
             }
+            OutputFormat::Json => {
+                output::print_json(&json!({ "store": &store.name, "tags": counts }))?;
+            }
+        }
+        Ok(())
+    }
 
-            // split
-            let splits = _filter.splitn(2, '/').collect::<Vec<_>>();
-            let (filter, filter_query) = (splits[0], splits[1]);
-
-            println!(
-                "{} | Searching '{}' on {} store with filter '{}' and filter value '{}'...",
-                style("fa").bold().dim(),
-                style(&query).bold().green().bright(),
-                style(store.name).bold().bright(),
-                style(&filter).bold().red().bright(),
-                style(&filter_query).bold().green().bright()
-            );
+    #[allow(clippy::too_many_arguments)]
+    fn command_search(
+        &mut self,
+        passed_query: &str,
+        mode: SearchMode,
+        passed_store: &Option<String>,
+        all_stores: bool,
+        passed_filter: &Option<String>,
+        tag_filter: Option<Filter>,
+        listing_args: &ListingArgs,
+        state: &FaApplicationState,
+    ) -> Result<(), FaError> {
+        let matcher = Matcher::new(passed_query, mode)?;
+        let filters = [
+            passed_filter.as_deref().map(Filter::parse).transpose()?,
+            tag_filter,
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+        let options = listing_args.apply(&state.configuration._inner.display, all_stores);
+        let format = self.cli.format;
 
-            for cred in store.data.iter() {
-                if cred.user.to_lowercase().starts_with(&query) {
-                    let site = match &cred.site {
-                        Some(s) => s,
-                        None => "-",
-                    };
-                    let tag = match &cred.tag {
-                        Some(t) => t,
-                        None => "-",
-                    };
-                    match filter {
-                        "site" => {
-                            if site.starts_with(filter_query) {
-                                println!(
-                                    "{} | {} | {} | {} | {}",
+        // every store is opened up front, so a broken one is skipped instead
+        // of ending the search.
+        let mut stores = Vec::new();
+        let mut failed_stores = Vec::new();
+        match all_stores {
+            true => {
+                let base_path = &state.configuration._inner.store.base_path;
+                for store_name in Store::list(base_path)? {
+                    let store_path = Store::get_file_path(&store_name, base_path)?;
+                    match Store::load(&store_name, store_path, &state.configuration) {
+                        Ok(store) => stores.push(store),
+                        Err(e) => {
+                            if format != OutputFormat::Json {
+                                eprintln!(
+                                    "{} | {} the {} store. {}",
                                     style("fa").bold().dim(),
-                                    cred.user,
-                                    cred.password,
-                                    site,
-                                    tag
+                                    style("Skipping").yellow(),
+                                    style(&store_name).bold().bright(),
+                                    e
                                 );
                             }
-                        }
-                        "tag" => {
-                            if tag.starts_with(filter_query) {
-                                println!(
-                                    "{} | {} | {} | {} | {}",
-                                    style("fa").bold().dim(),
-                                    cred.user,
-                                    cred.password,
-                                    site,
-                                    tag
-                                );
-                            }
-                        }
-                        _ => {
-                            println!("You know, this code should've never been executed. I think my code sucks.");
+                            failed_stores.push(json!({
+                                "store": store_name,
+                                "error": { "kind": e.kind(), "message": e.to_string() },
+                            }));
                         }
                     }
                 }
             }
+            false => stores.push(self.get_store(passed_store, state, false)?),
+        }
+
+        let store_names = stores
+            .iter()
+            .map(|store| store.name.as_str())
+            .collect::<Vec<_>>();
+        let searched = match all_stores {
+            true => format!("{} stores", style(store_names.len()).bold().bright()),
+            false => format!("{} store", style(store_names.join("")).bold().bright()),
+        };
+        match passed_filter {
+            None => output::status(
+                format,
+                format!(
+                    "{} | Searching '{}' on {}...",
+                    style("fa").bold().dim(),
+                    style(passed_query).bold().green().bright(),
+                    searched
+                ),
+            ),
+            Some(passed_filter) => output::status(
+                format,
+                format!(
+                    "{} | Searching '{}' on {} with filter '{}'...",
+                    style("fa").bold().dim(),
+                    style(passed_query).bold().green().bright(),
+                    searched,
+                    style(passed_filter).bold().red().bright()
+                ),
+            ),
         }
+
+        let entries = stores
+            .iter()
+            .flat_map(|store| {
+                store.data.iter().map(|cred| Entry {
+                    store: &store.name,
+                    credential: cred,
+                })
+            })
+            .filter(|entry| {
+                filters
+                    .iter()
+                    .all(|filter| filter.matches(entry.credential))
+            })
+            .collect::<Vec<_>>();
+        let json_fields = match all_stores {
+            true => json!({ "stores": store_names, "failed_stores": failed_stores }),
+            false => json!({ "store": store_names.join("") }),
+        };
+        output::print_credentials(
+            &search::search(&matcher, &entries),
+            &options,
+            format,
+            json_fields,
+        )
+    }
+
+    fn command_generate(
+        &self,
+        generator_args: &GeneratorArgs,
+        state: &FaApplicationState,
+    ) -> Result<(), FaError> {
+        // printed as is, so it can be piped elsewhere.
+        let password =
+            generator::generate(&generator_args.apply(&state.configuration._inner.generator))?;
+        println!("{}", password);
+        Ok(())
+    }
+
+    fn command_rekey(
+        &self,
+        passed_to: &str,
+        state: &mut FaApplicationState,
+    ) -> Result<(), FaError> {
+        let base_path = &state.configuration._inner.store.base_path;
+        let security = &state.configuration._inner.security;
+        if security.backend.format() != Format::OpenPgp {
+            return Err(FaError::RekeyUnsupported {
+                backend: security.backend.to_string(),
+            });
+        }
+
+        // verify new key.
+        let from = &security.gpg_fingerprint;
+        let mut new_security = security.clone();
+        new_security.gpg_fingerprint = passed_to.to_string();
+        let new_backend = crypto::from_config(&new_security)?;
+        if !new_backend.validate_identity()? {
+            return Err(FaError::InvalidFingerprint {
+                fingerprint: passed_to.to_string(),
+            });
+        }
+
+        // signatures are only trusted on a full fingerprint.
+        let to = &new_backend
+            .resolve_fingerprint(passed_to)?
+            .unwrap_or(passed_to.to_string());
+        new_security.gpg_fingerprint = to.clone();
+
+        // a rekey that failed midway left some stores on the new key already,
+        // signed by it. they are opened with the new key & re-encrypted again,
+        // so running it once more finishes the job.
+        let mut old_configuration = state.configuration.clone();
+        old_configuration
+            ._inner
+            .security
+            .trusted_signers
+            .push(to.clone());
+        let mut new_configuration = state.configuration.clone();
+        new_configuration._inner.security = new_security.clone();
+        new_configuration
+            ._inner
+            .security
+            .trusted_signers
+            .push(from.clone());
+
+        // re-encrypt and verify every store in memory first. loading doesn't
+        // write, so nothing is written unless all of them decrypt with the new
+        // key.
+        let mut rekeyed_stores = Vec::new();
+        for store_name in Store::list(base_path)? {
+            let store_path = Store::get_file_path(&store_name, base_path)?;
+            let mut store = match Store::load(&store_name, store_path.clone(), &old_configuration) {
+                Ok(store) => store,
+                Err(e) => {
+                    Store::load(&store_name, store_path, &new_configuration).map_err(|_| e)?
+                }
+            };
+            if store.format() != Format::OpenPgp {
+                println!(
+                    "{} | Skipping {} store. It is not encrypted with OpenPGP.",
+                    style("fa").bold().dim(),
+                    style(&store_name).bold().red().bright(),
+                );
+                continue;
+            }
+
+            store
+                .recipients
+                .retain(|r| !crypto::is_same_key(r, from) && !crypto::is_same_key(r, to));
+            store.recipients.insert(0, to.clone());
+            store.use_security(&new_security)?;
+            let contents = store.encrypt()?;
+            if !store.verify(&contents)? {
+                return Err(FaError::RekeyVerificationFailed { store: store_name });
+            }
+            rekeyed_stores.push((store, contents));
+        }
+
+        // the rewritten stores are signed by the new key. it's trusted before
+        // the first one is written, so they stay readable if the rekey stops
+        // midway.
+        let is_to_trusted = state
+            .configuration
+            ._inner
+            .security
+            .trusted_signers
+            .iter()
+            .any(|signer| crypto::is_same_key(signer, to));
+        if !is_to_trusted {
+            state
+                .configuration
+                ._inner
+                .security
+                .trusted_signers
+                .push(to.clone());
+            state.configuration.save()?;
+        }
+
+        for (store, contents) in rekeyed_stores.iter() {
+            let recipients_path = Store::get_recipients_file_path(Path::new(&store.path));
+            let written = store.write(contents).and_then(|_| {
+                match Store::check_if_exists(&recipients_path) {
+                    true => store.save_recipients(),
+                    false => Ok(()),
+                }
+            });
+            if let Err(e) = written {
+                eprintln!(
+                    "{} | Could not re-encrypt {} store. Run 'fa rekey --to {}' again to finish rekeying the rest.",
+                    style("fa").bold().dim(),
+                    style(&store.name).bold().red().bright(),
+                    to
+                );
+                return Err(e);
+            }
+            println!(
+                "{} | {} re-encrypted {} store.",
+                style("fa").bold().dim(),
+                style("Successfully").green(),
+                style(&store.name).bold().bright()
+            );
+        }
+
+        // only now switch over to the new key, which doesn't need to be a
+        // trusted signer anymore.
+        let security = &mut state.configuration._inner.security;
+        security.gpg_fingerprint = to.clone();
+        if !is_to_trusted {
+            security.trusted_signers.retain(|signer| signer != to);
+        }
+        state.configuration.save()?;
+        println!(
+            "{} | {} rekeyed {} stores to '{}'.",
+            style("fa").bold().dim(),
+            style("Successfully").green(),
+            style(rekeyed_stores.len()).bold().bright(),
+            style(to).bold().bright()
+        );
+
         Ok(())
     }
 
@@ -504,52 +1426,66 @@ impl Fa {
         let mut store = self.get_store(passed_store, state, true)?;
         let csv_file_path = Path::new(&passed_csv_path).absolutize()?;
         let mut csv_reader = csv::Reader::from_path(&csv_file_path)?;
-        let mut cred_count = 0;
+        let format = self.cli.format;
+        let mut imported = Vec::new();
+        let mut skipped = Vec::new();
 
         for record_result in csv_reader.deserialize() {
             let record: ParsedCredential = record_result?;
 
             // skip if exists.
-            if store
-                .data
-                .iter()
-                .any(|s| s.user.eq(&record.username) && s.password.eq(&record.password))
-            {
-                println!(
-                    "{} | Skipping '{}'. It as already present.",
-                    style("fa").bold().dim(),
-                    style(&record.username).bold().red().bright(),
+            let credential = Credential::new(
+                &record.username,
+                &record.password,
+                record.url,
+                BTreeSet::new(),
+            );
+            if store.is_duplicate(&credential) {
+                output::status(
+                    format,
+                    format!(
+                        "{} | Skipping '{}'. It as already present.",
+                        style("fa").bold().dim(),
+                        style(&record.username).bold().red().bright(),
+                    ),
                 );
+                skipped.push(record.username);
                 continue;
             }
 
             // does not exist, add.
-            store.data.push(Credential {
-                password: String::from(&record.password),
-                user: String::from(&record.username),
-                tag: None,
-                site: record.url,
-            });
-            cred_count += 1;
+            imported.push(json!({ "id": &credential.id, "user": &credential.user }));
+            store.data.push(credential);
 
             // tell user.
-            println!(
-                "{} | You've {} added '{}' login to {} store.",
-                style("fa").bold().dim(),
-                style("successfully").green(),
-                style(&record.username).bold().bright(),
-                style(&store.name).bold().bright()
+            output::status(
+                format,
+                format!(
+                    "{} | You've {} added '{}' login to {} store.",
+                    style("fa").bold().dim(),
+                    style("successfully").green(),
+                    style(&record.username).bold().bright(),
+                    style(&store.name).bold().bright()
+                ),
             );
         }
 
         // save store.
-        store.save(&state.configuration._inner.security.gpg_fingerprint)?;
+        store.save()?;
 
+        if format == OutputFormat::Json {
+            return output::print_json(&json!({
+                "store": &store.name,
+                "path": csv_file_path,
+                "imported": imported,
+                "skipped": skipped,
+            }));
+        }
         println!(
             "{} | {} imported {} credentials from csv file {} to {} store.",
             style("fa").bold().dim(),
             style("Successfully").green(),
-            style(cred_count).bold().bright(),
+            style(imported.len()).bold().bright(),
             style(&csv_file_path.to_str().ok_or(FaError::UnexpectedNone)?)
                 .bold()
                 .bright(),
@@ -563,42 +1499,58 @@ impl Fa {
         &mut self,
         passed_store: &Option<String>,
         passed_csv_path: &String,
+        passed_filter: &Option<String>,
         state: &FaApplicationState,
     ) -> Result<(), FaError> {
+        let filter = passed_filter.as_deref().map(Filter::parse).transpose()?;
         let store = self.get_store(passed_store, state, false)?;
         let csv_file_path = Path::new(&passed_csv_path).absolutize()?;
         let csv_file = File::options()
             .read(true)
             .write(true)
             .create(true)
+            .truncate(true)
             .append(false)
             .open(&csv_file_path)?;
         let mut csv_writer = csv::Writer::from_writer(csv_file);
-        let mut cred_count = 0;
+        let format = self.cli.format;
+        let mut exported = Vec::new();
 
         csv_writer.write_record(["username", "password", "url"])?;
-        for record in store.data.iter() {
-            let url = match record.site.clone() {
-                Some(u) => u,
-                None => String::new(),
-            };
+        for record in store
+            .data
+            .iter()
+            .filter(|cred| filter.as_ref().is_none_or(|filter| filter.matches(cred)))
+        {
+            let url = record.site.clone().unwrap_or_default();
             csv_writer.write_record([&record.user, &record.password, &url])?;
-            cred_count += 1;
-            println!(
-                "{} | You've {} exported '{}' login to {} store.",
-                style("fa").bold().dim(),
-                style("successfully").green(),
-                style(&record.user).bold().bright(),
-                style(&store.name).bold().bright()
+            exported.push(json!({ "id": &record.id, "user": &record.user }));
+            output::status(
+                format,
+                format!(
+                    "{} | You've {} exported '{}' login to {} store.",
+                    style("fa").bold().dim(),
+                    style("successfully").green(),
+                    style(&record.user).bold().bright(),
+                    style(&store.name).bold().bright()
+                ),
             );
         }
         csv_writer.flush()?;
 
+        if format == OutputFormat::Json {
+            return output::print_json(&json!({
+                "store": &store.name,
+                "path": csv_file_path,
+                "exported": exported,
+            }));
+        }
+
         println!(
             "{} | {} exported {} credentials from {} store to {}.",
             style("fa").bold().dim(),
             style("Successfully").green(),
-            style(cred_count).bold().bright(),
+            style(exported.len()).bold().bright(),
             style(&store.name).bold().bright(),
             style(&csv_file_path.to_str().ok_or(FaError::UnexpectedNone)?)
                 .bold()
@@ -610,32 +1562,66 @@ impl Fa {
 
     pub fn command_init(
         &self,
+        passed_backend: &Option<Backend>,
         passed_key_fingerprint: &Option<String>,
+        passed_age_identity: &Option<String>,
+        passed_age_recipients: &[String],
         passed_store: &Option<String>,
         passed_store_path: &Option<String>,
     ) -> Result<Config, FaError> {
-        // get fingerprint
-        let fingerprint: String = match passed_key_fingerprint {
-            Some(p_fgp) => {
-                // verify
-                if !Gpg::check_if_fingerprint_exists(p_fgp)? {
-                    Gpg::fingerprint_prompt_until_true_or_term()?
-                } else {
-                    p_fgp.to_owned()
-                }
-            }
-            None => {
-                // prompt
-                Gpg::fingerprint_prompt_until_true_or_term()?
+        // get backend
+        let backend = match (passed_backend, passed_key_fingerprint, passed_age_identity) {
+            (Some(p_backend), _, _) => *p_backend,
+            (None, Some(_), _) => Backend::Gpg,
+            (None, None, Some(_)) => Backend::Age,
+            (None, None, None) => {
+                let prompt_str = format!(
+                    "{} | {}Which backend would you like to encrypt/decrypt your stores with?",
+                    style("[1/4]").bold().dim(),
+                    LOCK
+                );
+                let backends = Backend::value_variants();
+                let selection = Select::new()
+                    .with_prompt(prompt_str)
+                    .items(backends)
+                    .default(0)
+                    .interact()?;
+                backends[selection]
             }
         };
 
+        // get key identity
+        let mut security = InnerConfigSecurity {
+            backend: backend,
+            gpg_fingerprint: passed_key_fingerprint.clone().unwrap_or_default(),
+            trusted_signers: Vec::new(),
+            keyring_path: None,
+            age_identity: passed_age_identity.clone(),
+            age_recipients: passed_age_recipients.to_vec(),
+        };
+        let is_identity_passed = match backend {
+            Backend::Gpg | Backend::Native => passed_key_fingerprint.is_some(),
+            Backend::Age => passed_age_identity.is_some(),
+        };
+        if !is_identity_passed || !crypto::from_config(&security)?.validate_identity()? {
+            security = Self::identity_prompt_until_valid(security)?;
+        }
+        if let Some(age_identity) = &security.age_identity {
+            let age_identity_path = Path::new(age_identity).absolutize()?;
+            security.age_identity = Some(
+                age_identity_path
+                    .to_str()
+                    .ok_or(FaError::UnexpectedNone)?
+                    .to_string(),
+            );
+        }
+
         let mut store_name: String = match passed_store {
             Some(p_store) => p_store.to_owned(),
             None => {
                 let prompt_str = format!(
                     "{} | {}What would you call your default credential store?",
-                    style("[2/3]").bold().dim(),
+                    style("[3/4]").bold().dim(),
                     MOAI
                 );
                 Input::new().with_prompt(prompt_str).interact_text()?
@@ -648,14 +1634,14 @@ impl Fa {
             None => {
                 let prompt_str = format!(
                     "{} | {}Where would all the stores be located at?",
-                    style("[3/3]").bold().dim(),
+                    style("[4/4]").bold().dim(),
                     ROAD
                 );
                 Input::new().with_prompt(prompt_str).interact_text()?
             }
         };
 
-        let config = Config::new(store_path, store_name.clone(), fingerprint)?; //
+        let config = Config::new(store_path, store_name.clone(), security)?;
         println!("{} | {}Successfully {} a config. You can now run '{}' to add a new credential to {} store.",
                  style("fa").bold().dim(),
                  SPARKLE,
@@ -664,4 +1650,46 @@ impl Fa {
                  style(store_name).bright());
         Ok(config)
     }
+
+    fn identity_prompt_until_valid(
+        security: InnerConfigSecurity,
+    ) -> Result<InnerConfigSecurity, FaError> {
+        let with_identity = |identity: &String| -> InnerConfigSecurity {
+            let mut security = security.clone();
+            match security.backend {
+                Backend::Gpg | Backend::Native => security.gpg_fingerprint = identity.clone(),
+                Backend::Age => security.age_identity = Some(identity.clone()),
+            };
+            security
+        };
+        let question = match security.backend {
+            Backend::Gpg | Backend::Native => {
+                "What GPG Key would you like to use to encrypt/decrypt your stores (fingerprint/keyid)?"
+            }
+            Backend::Age => {
+                "Where is the age identity (or ssh private key) you would like to use to encrypt/decrypt your stores?"
+            }
+        };
+        let prompt_str = format!("{} | {}{}", style("[2/4]").bold().dim(), KEY, question);
+        let identity = Input::<String>::new()
+            .with_prompt(&prompt_str)
+            .validate_with(|input: &String| -> Result<(), FaError> {
+                match crypto::from_config(&with_identity(input))?.validate_identity() {
+                    Ok(true) => Ok(()),
+                    // asking again won't help, so it fails below instead.
+                    Err(FaError::NoRecipients) => Ok(()),
+                    Ok(false) => match security.backend {
+                        Backend::Gpg | Backend::Native => Err(FaError::InvalidFingerprint {
+                            fingerprint: input.clone(),
+                        }),
+                        Backend::Age => Err(FaError::NoAgeIdentity { path: input.into() }),
+                    },
+                    Err(e) => Err(e),
+                }
+            })
+            .interact_text()?;
+        let security = with_identity(&identity);
+        crypto::from_config(&security)?.validate_identity()?;
+        Ok(security)
+    }
 }
@@ -0,0 +1,424 @@
+use crate::{error::FaError, store::Credential};
+use regex::{Regex, RegexBuilder};
+
+/// The fields a condition can be about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Id,
+    User,
+    Site,
+    Tag,
+}
+
+impl Field {
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "id" => Some(Field::Id),
+            "user" => Some(Field::User),
+            "site" => Some(Field::Site),
+            "tag" | "tags" => Some(Field::Tag),
+            _ => None,
+        }
+    }
+}
+
+/// How the value of a condition is compared.
+#[derive(Debug, Clone)]
+enum Operator {
+    Contains(String),
+    Equals(String),
+    Pattern(Regex),
+}
+
+impl Operator {
+    fn matches(&self, value: &str) -> bool {
+        match self {
+            Operator::Contains(expected) => value.to_lowercase().contains(expected.as_str()),
+            Operator::Equals(expected) => value.to_lowercase() == *expected,
+            Operator::Pattern(regex) => regex.is_match(value),
+        }
+    }
+}
+
+/// a parsed filter, a small query language to select credentials, e.g.
+/// `site:github.com tag:work -tag:old user:ci*`.
+///
+/// conditions next to each other must all match. `OR` matches either side,
+/// `NOT` or a leading `-` negates a condition and parentheses group them. a
+/// condition is `<field><operator><value>` or just a value, which is looked
+/// for in the user, site and tag.
+///
+/// - `field:value` contains the value, or matches it as a glob if it has a
+///   `*` or `?` in it.
+/// - `field=value` is the value. `field=` has no value at all.
+/// - `field~value` matches the value as a regular expression.
+/// - `field/value` starts with the value. it is what `--filter` used to take.
+///
+/// a tag condition matches if any of the tags of a credential match.
+///
+/// every operator ignores the case. values can be put into double quotes to
+/// keep spaces and parentheses in them.
+#[derive(Debug, Clone)]
+pub struct Filter {
+    expression: Expression,
+}
+
+#[derive(Debug, Clone)]
+enum Expression {
+    And(Box<Expression>, Box<Expression>),
+    Or(Box<Expression>, Box<Expression>),
+    Not(Box<Expression>),
+    Condition {
+        // None looks at the user, site and tag.
+        field: Option<Field>,
+        operator: Operator,
+        // 'field=' only matches credentials without the field.
+        matches_missing: bool,
+    },
+}
+
+impl Filter {
+    pub fn parse(input: &str) -> Result<Self, FaError> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser {
+            tokens: tokens,
+            position: 0,
+        };
+        let expression = parser.parse_or()?;
+        match parser.peek() {
+            None => Ok(Filter {
+                expression: expression,
+            }),
+            Some((column, token)) => Err(invalid(
+                *column,
+                &token.to_string(),
+                "there is no '(' to close",
+            )),
+        }
+    }
+
+    /// a filter of the credentials with any of the tags, or all of them.
+    /// there is none without any tags.
+    pub fn tags(tags: &[String], all: bool) -> Option<Self> {
+        tags.iter()
+            .map(|tag| Expression::Condition {
+                field: Some(Field::Tag),
+                operator: Operator::Equals(tag.trim().to_lowercase()),
+                matches_missing: false,
+            })
+            .reduce(|left, right| match all {
+                true => Expression::And(Box::new(left), Box::new(right)),
+                false => Expression::Or(Box::new(left), Box::new(right)),
+            })
+            .map(|expression| Filter {
+                expression: expression,
+            })
+    }
+
+    pub fn matches(&self, credential: &Credential) -> bool {
+        self.expression.matches(credential)
+    }
+}
+
+impl Expression {
+    fn matches(&self, credential: &Credential) -> bool {
+        match self {
+            Expression::And(left, right) => left.matches(credential) && right.matches(credential),
+            Expression::Or(left, right) => left.matches(credential) || right.matches(credential),
+            Expression::Not(inner) => !inner.matches(credential),
+            Expression::Condition {
+                field,
+                operator,
+                matches_missing,
+            } => {
+                let values = field_values(*field, credential);
+                match (*matches_missing, values.is_empty()) {
+                    (true, true) => true,
+                    (true, false) => false,
+                    (false, _) => values.iter().any(|value| operator.matches(value)),
+                }
+            }
+        }
+    }
+}
+
+/// the values of a field. a missing site or tag has none.
+fn field_values(field: Option<Field>, credential: &Credential) -> Vec<&str> {
+    match field {
+        Some(Field::Id) => vec![credential.id.as_str()],
+        Some(Field::User) => vec![credential.user.as_str()],
+        Some(Field::Site) => credential.site.iter().map(String::as_str).collect(),
+        Some(Field::Tag) => credential.tags.iter().map(String::as_str).collect(),
+        None => [Field::User, Field::Site, Field::Tag]
+            .into_iter()
+            .flat_map(|field| field_values(Some(field), credential))
+            .collect(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    // '-' or 'NOT'.
+    Not(String),
+    Term {
+        field: Option<String>,
+        operator: char,
+        value: String,
+        text: String,
+    },
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+            Token::And => write!(f, "AND"),
+            Token::Or => write!(f, "OR"),
+            Token::Not(text) => write!(f, "{}", text),
+            Token::Term { text, .. } => write!(f, "{}", text),
+        }
+    }
+}
+
+const OPERATORS: [char; 4] = [':', '=', '~', '/'];
+
+/// split the input into tokens, each with the column it starts at.
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, FaError> {
+    let characters = input.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < characters.len() {
+        let column = i + 1;
+        match characters[i] {
+            c if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push((column, Token::Open));
+                i += 1;
+            }
+            ')' => {
+                tokens.push((column, Token::Close));
+                i += 1;
+            }
+            '-' if i + 1 < characters.len() && !characters[i + 1].is_whitespace() => {
+                tokens.push((column, Token::Not("-".to_string())));
+                i += 1;
+            }
+            _ => {
+                let start = i;
+                let mut field = None;
+                let mut operator = ':';
+                let mut value = String::new();
+                let mut quoted = false;
+
+                while i < characters.len() {
+                    let c = characters[i];
+                    if c.is_whitespace() || c == '(' || c == ')' {
+                        break;
+                    }
+                    if c == '"' {
+                        let quote_column = i + 1;
+                        i += 1;
+                        while i < characters.len() && characters[i] != '"' {
+                            value.push(characters[i]);
+                            i += 1;
+                        }
+                        if i == characters.len() {
+                            return Err(invalid(quote_column, "\"", "the quote is never closed"));
+                        }
+                        quoted = true;
+                        i += 1;
+                        continue;
+                    }
+                    // the first operator after a plain name splits off the field.
+                    let is_field_name =
+                        !value.is_empty() && value.chars().all(|c| c.is_ascii_alphabetic());
+                    if field.is_none() && !quoted && OPERATORS.contains(&c) && is_field_name {
+                        field = Some(std::mem::take(&mut value));
+                        operator = c;
+                    } else {
+                        value.push(c);
+                    }
+                    i += 1;
+                }
+
+                let text = characters[start..i].iter().collect::<String>();
+                let token = match (field.is_none() && !quoted, value.as_str()) {
+                    (true, "AND") => Token::And,
+                    (true, "OR") => Token::Or,
+                    (true, "NOT") => Token::Not(value),
+                    _ => Token::Term {
+                        field: field,
+                        operator: operator,
+                        value: value,
+                        text: text,
+                    },
+                };
+                tokens.push((column, token));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&(usize, Token)> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<(usize, Token)> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<Expression, FaError> {
+        let mut filter = self.parse_and()?;
+        while let Some((_, Token::Or)) = self.peek() {
+            self.next();
+            let right = self.parse_and()?;
+            filter = Expression::Or(Box::new(filter), Box::new(right));
+        }
+        Ok(filter)
+    }
+
+    fn parse_and(&mut self) -> Result<Expression, FaError> {
+        let mut filter = self.parse_not()?;
+        loop {
+            match self.peek() {
+                Some((_, Token::And)) => {
+                    self.next();
+                }
+                Some((_, Token::Open | Token::Not(_) | Token::Term { .. })) => (),
+                _ => break,
+            }
+            let right = self.parse_not()?;
+            filter = Expression::And(Box::new(filter), Box::new(right));
+        }
+        Ok(filter)
+    }
+
+    fn parse_not(&mut self) -> Result<Expression, FaError> {
+        match self.peek() {
+            Some((_, Token::Not(_))) => {
+                self.next();
+                Ok(Expression::Not(Box::new(self.parse_not()?)))
+            }
+            _ => self.parse_atom(),
+        }
+    }
+
+    fn parse_atom(&mut self) -> Result<Expression, FaError> {
+        match self.next() {
+            Some((column, Token::Open)) => {
+                let filter = self.parse_or()?;
+                match self.next() {
+                    Some((_, Token::Close)) => Ok(filter),
+                    _ => Err(invalid(column, "(", "the parenthesis is never closed")),
+                }
+            }
+            Some((
+                column,
+                Token::Term {
+                    field,
+                    operator,
+                    value,
+                    text,
+                },
+            )) => condition(column, &text, field.as_deref(), operator, &value),
+            Some((column, token)) => Err(invalid(
+                column,
+                &token.to_string(),
+                "expected a condition here",
+            )),
+            None => match self.tokens.last() {
+                Some((column, token)) => Err(invalid(
+                    *column,
+                    &token.to_string(),
+                    "expected a condition after it",
+                )),
+                None => Err(invalid(1, "", "the filter is empty")),
+            },
+        }
+    }
+}
+
+fn condition(
+    column: usize,
+    text: &str,
+    field_name: Option<&str>,
+    operator: char,
+    value: &str,
+) -> Result<Expression, FaError> {
+    let field = match field_name {
+        None => None,
+        Some(name) => Some(Field::parse(name).ok_or_else(|| {
+            invalid(
+                column,
+                text,
+                &format!(
+                    "'{}' is not a field. Use one of 'id', 'user', 'site' or 'tag'",
+                    name
+                ),
+            )
+        })?),
+    };
+    if value.is_empty() && operator != '=' {
+        return Err(invalid(column, text, "expected a value"));
+    }
+
+    // a regex is case insensitive already, lowercasing it would change the
+    // meaning of escapes like '\D'.
+    let pattern = value;
+    let value = value.to_lowercase();
+    let is_glob = value.contains('*') || value.contains('?');
+    let operator = match operator {
+        '=' => Operator::Equals(value.clone()),
+        '~' => Operator::Pattern(
+            RegexBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .map_err(|_| invalid(column, text, "the value is not a regular expression"))?,
+        ),
+        '/' => Operator::Pattern(glob(&format!("{}*", value))?),
+        _ if is_glob => Operator::Pattern(glob(&value)?),
+        _ => Operator::Contains(value.clone()),
+    };
+    Ok(Expression::Condition {
+        field: field,
+        operator: operator,
+        matches_missing: value.is_empty(),
+    })
+}
+
+/// a regex that matches the whole value against a glob of '*' & '?'.
+fn glob(pattern: &str) -> Result<Regex, FaError> {
+    let mut regex = String::from("^");
+    for c in pattern.chars() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    Ok(RegexBuilder::new(&regex).case_insensitive(true).build()?)
+}
+
+fn invalid(column: usize, token: &str, reason: &str) -> FaError {
+    FaError::InvalidFilter {
+        column: column,
+        token: token.to_string(),
+        reason: reason.to_string(),
+    }
+}
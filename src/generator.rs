@@ -0,0 +1,240 @@
+use crate::{config::InnerConfigGenerator, error::FaError};
+use rand::{rngs::OsRng, seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display};
+
+/// The EFF short wordlist (https://www.eff.org/dice), one word per line.
+/// It is licensed under CC BY 3.0 by the Electronic Frontier Foundation.
+const WORDLIST: &str = include_str!("wordlist.txt");
+
+/// characters that are easily mistaken for one another.
+const AMBIGUOUS_CHARACTERS: &str = "0Oo1lI;:,.";
+
+/// The kinds of characters a generated password can be made of.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize, clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum CharacterClass {
+    Lower,
+    Upper,
+    Digit,
+    Symbol,
+}
+
+impl CharacterClass {
+    fn characters(&self) -> &'static str {
+        match self {
+            CharacterClass::Lower => "abcdefghijklmnopqrstuvwxyz",
+            CharacterClass::Upper => "ABCDEFGHIJKLMNOPQRSTUVWXYZ",
+            CharacterClass::Digit => "0123456789",
+            CharacterClass::Symbol => "!@#$%^&*()-_=+[]{};:,.<>/?~",
+        }
+    }
+}
+
+impl Display for CharacterClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CharacterClass::Lower => write!(f, "lower"),
+            CharacterClass::Upper => write!(f, "upper"),
+            CharacterClass::Digit => write!(f, "digit"),
+            CharacterClass::Symbol => write!(f, "symbol"),
+        }
+    }
+}
+
+/// generate a password or a passphrase, depending on the options.
+pub fn generate(options: &InnerConfigGenerator) -> Result<String, FaError> {
+    match options.passphrase {
+        true => generate_passphrase(options),
+        false => generate_password(options),
+    }
+}
+
+fn generate_password(options: &InnerConfigGenerator) -> Result<String, FaError> {
+    if options.length == 0 {
+        return Err(FaError::InvalidGeneratorOptions {
+            reason: "a password needs at least one character".to_string(),
+        });
+    }
+    let characters_of = |class: &CharacterClass| {
+        class
+            .characters()
+            .chars()
+            .filter(|c| !options.exclude_ambiguous || !AMBIGUOUS_CHARACTERS.contains(*c))
+            .collect::<Vec<_>>()
+    };
+    let mut characters = options
+        .classes
+        .iter()
+        .flat_map(characters_of)
+        .collect::<Vec<_>>();
+    characters.sort_unstable();
+    characters.dedup();
+    if characters.is_empty() {
+        return Err(FaError::InvalidGeneratorOptions {
+            reason: "there are no characters to pick from".to_string(),
+        });
+    }
+
+    let mut required = options.require.clone();
+    required.sort_unstable();
+    required.dedup();
+    if let Some(class) = required.iter().find(|c| !options.classes.contains(c)) {
+        return Err(FaError::InvalidGeneratorOptions {
+            reason: format!("the required class '{}' is not one of the classes", class),
+        });
+    }
+    if required.len() > options.length {
+        return Err(FaError::InvalidGeneratorOptions {
+            reason: format!(
+                "a password of {} characters can't contain all the {} required classes",
+                options.length,
+                required.len()
+            ),
+        });
+    }
+
+    // one character of every required class, the rest from all of them.
+    let mut password = Vec::with_capacity(options.length);
+    for class in required.iter() {
+        let class_characters = characters_of(class);
+        password.push(
+            *class_characters
+                .choose(&mut OsRng)
+                .ok_or(FaError::UnexpectedNone)?,
+        );
+    }
+    while password.len() < options.length {
+        password.push(characters[OsRng.gen_range(0..characters.len())]);
+    }
+    password.shuffle(&mut OsRng);
+    Ok(password.into_iter().collect())
+}
+
+fn generate_passphrase(options: &InnerConfigGenerator) -> Result<String, FaError> {
+    if options.words == 0 {
+        return Err(FaError::InvalidGeneratorOptions {
+            reason: "a passphrase needs at least one word".to_string(),
+        });
+    }
+    let words = WORDLIST.lines().collect::<Vec<_>>();
+    let passphrase = (0..options.words)
+        .map(|_| words[OsRng.gen_range(0..words.len())])
+        .collect::<Vec<_>>()
+        .join(&options.separator);
+    Ok(passphrase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> InnerConfigGenerator {
+        InnerConfigGenerator::default()
+    }
+
+    fn has_class(password: &str, class: CharacterClass) -> bool {
+        password.chars().any(|c| class.characters().contains(c))
+    }
+
+    #[test]
+    fn passwords_have_the_length() {
+        for length in [1, 8, 64] {
+            let options = InnerConfigGenerator {
+                length: length,
+                ..options()
+            };
+            assert_eq!(generate(&options).unwrap().chars().count(), length);
+        }
+    }
+
+    #[test]
+    fn passwords_contain_every_required_class() {
+        let required = [CharacterClass::Digit, CharacterClass::Symbol];
+        let options = InnerConfigGenerator {
+            length: 2,
+            require: required.to_vec(),
+            ..options()
+        };
+        // two characters out of four classes would miss them often otherwise.
+        for _ in 0..100 {
+            let password = generate(&options).unwrap();
+            assert!(required.iter().all(|class| has_class(&password, *class)));
+        }
+    }
+
+    #[test]
+    fn passwords_only_use_the_classes() {
+        let options = InnerConfigGenerator {
+            length: 200,
+            classes: vec![CharacterClass::Digit],
+            ..options()
+        };
+        let password = generate(&options).unwrap();
+        assert!(password.chars().all(|c| c.is_ascii_digit()));
+    }
+
+    #[test]
+    fn ambiguous_characters_can_be_excluded() {
+        let options = InnerConfigGenerator {
+            length: 500,
+            exclude_ambiguous: true,
+            ..options()
+        };
+        let password = generate(&options).unwrap();
+        assert!(!password.chars().any(|c| AMBIGUOUS_CHARACTERS.contains(c)));
+    }
+
+    #[test]
+    fn passphrases_have_the_word_count() {
+        let options = InnerConfigGenerator {
+            passphrase: true,
+            words: 4,
+            separator: " ".to_string(),
+            ..options()
+        };
+        let passphrase = generate(&options).unwrap();
+        let words = passphrase.split(' ').collect::<Vec<_>>();
+        assert_eq!(words.len(), 4);
+        assert!(words
+            .iter()
+            .all(|word| WORDLIST.lines().any(|w| w == *word)));
+    }
+
+    #[test]
+    fn impossible_options_are_rejected() {
+        let invalid = [
+            InnerConfigGenerator {
+                length: 0,
+                ..options()
+            },
+            InnerConfigGenerator {
+                classes: Vec::new(),
+                ..options()
+            },
+            InnerConfigGenerator {
+                length: 1,
+                require: vec![CharacterClass::Lower, CharacterClass::Upper],
+                ..options()
+            },
+            InnerConfigGenerator {
+                classes: vec![CharacterClass::Lower],
+                require: vec![CharacterClass::Digit],
+                ..options()
+            },
+            InnerConfigGenerator {
+                passphrase: true,
+                words: 0,
+                ..options()
+            },
+        ];
+        for options in invalid.iter() {
+            assert!(matches!(
+                generate(options),
+                Err(FaError::InvalidGeneratorOptions { .. })
+            ));
+        }
+    }
+}
@@ -1,102 +1,200 @@
-use crate::{error::FaError, fa::KEY, store::Credential};
-use console::style;
-use dialoguer::Input;
+use crate::{
+    crypto::{is_trusted_signer, CryptoBackend, Format},
+    error::FaError,
+};
 use std::{
     io::Write,
     process::{Command, Stdio},
 };
 
-pub struct Gpg;
+/// A backend that shells out to the 'gpg' binary.
+#[derive(Debug, Clone)]
+pub struct Gpg {
+    fingerprint: String,
+    trusted_signers: Vec<String>,
+}
+
+/// The signatures 'gpg' reported on its status fd while decrypting.
+#[derive(Debug, Default)]
+struct GpgSignatures {
+    /// (signing key fingerprint, primary key fingerprint)
+    valid: Vec<(String, String)>,
+    /// key ids of signatures that did not verify.
+    bad: Vec<String>,
+    /// key ids of signatures that could not be checked, e.g. missing keys.
+    unchecked: Vec<String>,
+}
+
+impl GpgSignatures {
+    fn from_status(status: &str) -> Self {
+        let mut signatures = Self::default();
+        for line in status.lines() {
+            let mut fields = match line.strip_prefix("[GNUPG:] ") {
+                Some(line) => line.split_whitespace(),
+                None => continue,
+            };
+            let keyword = fields.next().unwrap_or_default();
+            let fields = fields.map(String::from).collect::<Vec<_>>();
+            let key = fields.first().cloned().unwrap_or_default();
+            match keyword {
+                "VALIDSIG" => {
+                    let primary_key = fields.get(9).cloned().unwrap_or(key.clone());
+                    signatures.valid.push((key, primary_key))
+                }
+                "BADSIG" => signatures.bad.push(key),
+                "ERRSIG" => signatures.unchecked.push(key),
+                _ => {}
+            }
+        }
+        signatures
+    }
+
+    fn is_trusted(&self, trusted_signers: &[String]) -> bool {
+        self.bad.is_empty()
+            && self.valid.iter().any(|(key, primary_key)| {
+                is_trusted_signer(trusted_signers, key)
+                    || is_trusted_signer(trusted_signers, primary_key)
+            })
+    }
+
+    fn signer(&self) -> String {
+        match (self.bad.first(), self.valid.first(), self.unchecked.first()) {
+            (Some(key), _, _) => format!("'{}' with a bad signature", key),
+            (None, Some((_, primary_key)), _) => format!("'{}'", primary_key),
+            (None, None, Some(key)) => format!("the unknown key '{}'", key),
+            (None, None, None) => "no one".to_string(),
+        }
+    }
+}
 
 impl Gpg {
-    pub fn decrypt(fingerprint: &String, data: Vec<u8>) -> Result<Vec<Credential>, FaError> {
-        // decrypt.
-        let gpg_decrypt = Command::new("gpg")
-            .args(["--quiet", "--local-user", fingerprint, "--decrypt", "--yes"])
+    pub fn new(fingerprint: &str, trusted_signers: Vec<String>) -> Self {
+        Self {
+            fingerprint: fingerprint.to_owned(),
+            trusted_signers: trusted_signers,
+        }
+    }
+
+    pub fn check_if_fingerprint_exists(fingerprint: &str) -> Result<bool, FaError> {
+        if fingerprint.len() < 2 {
+            return Ok(false);
+        }
+        let exit_status = Command::new("gpg")
+            .args(["-no-tty", "--fingerprint", fingerprint])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()?;
+        Ok(exit_status.success())
+    }
+}
+
+impl CryptoBackend for Gpg {
+    fn encrypt(&self, recipients: &[String], data: &[u8]) -> Result<Vec<u8>, FaError> {
+        // recipients are pinned by their fingerprint in the store's recipient
+        // list, so they don't need to be certified in the gpg web of trust.
+        let recipient_args = recipients
+            .iter()
+            .flat_map(|recipient| ["--recipient", recipient.as_str()]);
+        let gpg_encrypt = Command::new("gpg")
+            .args(["--quiet", "--trust-model", "always"])
+            .args(["--local-user", &self.fingerprint])
+            .args(recipient_args)
+            .args(["--sign", "--encrypt", "--yes"])
             .stderr(Stdio::null())
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .spawn()?;
-        gpg_decrypt
+        gpg_encrypt
             .stdin
             .as_ref()
             .ok_or(FaError::UnexpectedNone)?
-            .write_all(&data)?;
-        let output = gpg_decrypt.wait_with_output()?;
+            .write_all(data)?;
+
+        let output = gpg_encrypt.wait_with_output()?;
         if output.status.code().ok_or(FaError::UnexpectedNone)? != 0 {
-            return Err(FaError::GPGDecryptionError);
+            return Err(FaError::GPGEncryptionError);
         }
 
-        let output = String::from_utf8(output.stdout)?;
-
-        // transform data.
-        let store_data = match output.is_empty() {
-            true => Vec::new(),
-            false => serde_json::from_str::<Vec<Credential>>(&output)?,
-        };
-
-        Ok(store_data)
+        Ok(output.stdout)
     }
 
-    pub fn encrypt(fingerprint: &String, data: &String) -> Result<Vec<u8>, FaError> {
-        let gpg_encrypt = Command::new("gpg")
-            .args([
-                "--quiet",
-                "--local-user",
-                fingerprint,
-                "--default-recipient",
-                fingerprint,
-                "--sign",
-                "--encrypt",
-                "--yes",
-            ])
-            .stderr(Stdio::null())
+    fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, FaError> {
+        // the status lines on stderr tell us who signed the store.
+        let gpg_decrypt = Command::new("gpg")
+            .args(["--quiet", "--status-fd", "2"])
+            .args(["--local-user", &self.fingerprint])
+            .args(["--decrypt", "--yes"])
+            .stderr(Stdio::piped())
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .spawn()?;
-        gpg_encrypt
+        gpg_decrypt
             .stdin
             .as_ref()
             .ok_or(FaError::UnexpectedNone)?
-            .write_all(data.as_bytes())?;
+            .write_all(data)?;
+        let output = gpg_decrypt.wait_with_output()?;
+        let signatures = GpgSignatures::from_status(&String::from_utf8_lossy(&output.stderr));
 
-        let output = gpg_encrypt.wait_with_output()?;
-        if output.status.code().ok_or(FaError::UnexpectedNone)? != 0 {
-            return Err(FaError::GPGEncryptionError);
+        // a bad or unknown signature also makes gpg exit with an error, so
+        // it's checked first to tell a tampered or foreign store apart from
+        // one we can't decrypt.
+        let exit_code = output.status.code().ok_or(FaError::UnexpectedNone)?;
+        if exit_code != 0 && signatures.bad.is_empty() && signatures.unchecked.is_empty() {
+            return Err(FaError::GPGDecryptionError);
+        }
+
+        let mut trusted_signers = vec![self.fingerprint.clone()];
+        trusted_signers.extend(self.trusted_signers.iter().cloned());
+        if !signatures.is_trusted(&trusted_signers) {
+            return Err(FaError::UntrustedSignature {
+                signer: signatures.signer(),
+            });
         }
 
         Ok(output.stdout)
     }
 
-    pub fn check_if_fingerprint_exists(fingerprint: &String) -> Result<bool, FaError> {
-        if fingerprint.len() < 2 {
-            return Ok(false);
+    fn validate_identity(&self) -> Result<bool, FaError> {
+        Self::check_if_fingerprint_exists(&self.fingerprint)
+    }
+
+    fn default_recipients(&self) -> Result<Vec<String>, FaError> {
+        Ok(vec![self.fingerprint.clone()])
+    }
+
+    fn format(&self) -> Format {
+        Format::OpenPgp
+    }
+
+    fn resolve_fingerprint(&self, key: &str) -> Result<Option<String>, FaError> {
+        if key.trim().is_empty() {
+            return Ok(None);
         }
-        let exit_status = Command::new("gpg")
-            .args(["-no-tty", "--fingerprint", fingerprint])
-            .stdout(Stdio::null())
+        let output = Command::new("gpg")
+            .args(["--batch", "--with-colons", "--list-keys", "--", key])
             .stderr(Stdio::null())
-            .status()?;
-        Ok(exit_status.success())
-    }
+            .output()?;
 
-    pub fn fingerprint_prompt_until_true_or_term() -> Result<String, FaError> {
-        let prompt_str = format!(
-            "{} | {}What GPG Key would you like to use to encrypt/decrypt your stores (fingerprint/keyid)?",
-            style("[1/3]").bold().dim(),
-            KEY
-        );
-        Input::<String>::new()
-            .with_prompt(&prompt_str)
-            .validate_with(|input: &String| -> Result<(), FaError> {
-                match !Self::check_if_fingerprint_exists(input)? {
-                    true => Err(FaError::InvalidFingerprint {
-                        fingerprint: input.clone(),
-                    }),
-                    false => Ok(()),
+        // the fingerprint of a primary key is the 'fpr' line right after its
+        // 'pub' line, the ones of its subkeys follow their 'sub' lines.
+        let mut fingerprints = Vec::new();
+        let mut is_primary_key = false;
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            let fields = line.split(':').collect::<Vec<_>>();
+            match (fields[0], is_primary_key) {
+                ("pub", _) => is_primary_key = true,
+                ("fpr", true) => {
+                    fingerprints.extend(fields.get(9).map(|f| f.to_string()));
+                    is_primary_key = false;
                 }
-            })
-            .interact_text()
-            .map_err(|e| FaError::PromptError { source: e })
+                ("sub", _) => is_primary_key = false,
+                _ => {}
+            }
+        }
+        match fingerprints.as_slice() {
+            [fingerprint] => Ok(Some(fingerprint.clone())),
+            _ => Ok(None),
+        }
     }
 }
@@ -5,16 +5,31 @@
 //! of 'fa', run it and also print the bubbled (recoverable) errors.
 
 use fa::Fa;
+use output::OutputFormat;
 
+mod age_backend;
 mod cli;
+mod clipboard;
 mod config;
+mod crypto;
 mod error;
 mod fa;
+mod filter;
+mod generator;
 mod gpg;
+mod native;
+mod output;
+mod search;
 mod store;
 
-fn main() -> Result<(), String> {
+fn main() {
     std::env::set_var("RUST_BACKTRACE", "1");
     let mut fa = Fa::new();
-    fa.run().map_err(|e| e.to_string())
+    if let Err(e) = fa.run() {
+        match fa.format() {
+            OutputFormat::Json => output::print_json_error(&e),
+            _ => eprintln!("Error: {:?}", e.to_string()),
+        }
+        std::process::exit(e.exit_code());
+    }
 }
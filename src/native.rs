@@ -0,0 +1,278 @@
+use crate::{
+    crypto::{is_trusted_signer, CryptoBackend, Format},
+    error::FaError,
+};
+use pgp::{
+    composed::{
+        Message, MessageBuilder, PublicOrSecret, SignedPublicKey, SignedPublicSubKey,
+        SignedSecretKey, VerificationResult,
+    },
+    crypto::{hash::HashAlgorithm, sym::SymmetricKeyAlgorithm},
+    types::{CompressionAlgorithm, KeyDetails, Password, VerifyingKey},
+};
+use std::{
+    cell::OnceCell,
+    fmt,
+    fs::{self, File},
+    path::PathBuf,
+};
+
+/// A backend that speaks OpenPGP in-process instead of spawning 'gpg'. The
+/// keys are read from a keyring file (armored or binary), such as the output
+/// of 'gpg --export-secret-keys --armor <fingerprint>'. The produced messages
+/// are signed, compressed & encrypted just like the ones 'gpg' writes, so
+/// both backends can open each other's stores.
+pub struct NativePgp {
+    fingerprint: String,
+    keyring_path: PathBuf,
+    trusted_signers: Vec<String>,
+    passphrase: OnceCell<String>,
+}
+
+impl fmt::Debug for NativePgp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NativePgp")
+            .field("fingerprint", &self.fingerprint)
+            .field("keyring_path", &self.keyring_path)
+            .field("trusted_signers", &self.trusted_signers)
+            .finish()
+    }
+}
+
+impl NativePgp {
+    pub fn new(fingerprint: &str, keyring_path: PathBuf, trusted_signers: Vec<String>) -> Self {
+        Self {
+            fingerprint: fingerprint.to_owned(),
+            keyring_path: keyring_path,
+            trusted_signers: trusted_signers,
+            passphrase: OnceCell::new(),
+        }
+    }
+
+    fn read_keyring(&self) -> Result<Vec<PublicOrSecret>, FaError> {
+        if fs::metadata(&self.keyring_path).is_err() {
+            return Err(FaError::NoKeyring {
+                path: self.keyring_path.clone(),
+            });
+        }
+        let (keys, _) = PublicOrSecret::from_reader_many(File::open(&self.keyring_path)?)?;
+        let keys = keys.collect::<Result<Vec<_>, _>>()?;
+        Ok(keys)
+    }
+
+    /// a fingerprint/key id matches if it is a (case insensitive) suffix of
+    /// the full fingerprint, which is how 'gpg' resolves them as well.
+    fn matches(key: &impl KeyDetails, query: &str) -> bool {
+        let query = query
+            .trim_start_matches("0x")
+            .replace(' ', "")
+            .to_uppercase();
+        !query.is_empty() && format!("{:X}", key.fingerprint()).ends_with(&query)
+    }
+
+    fn secret_key(&self) -> Result<SignedSecretKey, FaError> {
+        self.read_keyring()?
+            .into_iter()
+            .find_map(|key| match key {
+                PublicOrSecret::Secret(k) if Self::matches(&k.primary_key, &self.fingerprint) => {
+                    Some(k)
+                }
+                _ => None,
+            })
+            .ok_or(FaError::InvalidFingerprint {
+                fingerprint: self.fingerprint.clone(),
+            })
+    }
+
+    /// the public key of a recipient. it can either be present as a public
+    /// key or as a secret key in the keyring.
+    fn public_key(keys: &[PublicOrSecret], fingerprint: &str) -> Result<SignedPublicKey, FaError> {
+        keys.iter()
+            .find_map(|key| match key {
+                PublicOrSecret::Public(k) if Self::matches(&k.primary_key, fingerprint) => {
+                    Some(k.clone())
+                }
+                PublicOrSecret::Secret(k) if Self::matches(&k.primary_key, fingerprint) => {
+                    Some(k.to_public_key())
+                }
+                _ => None,
+            })
+            .ok_or(FaError::InvalidFingerprint {
+                fingerprint: fingerprint.to_string(),
+            })
+    }
+
+    fn encryption_subkey(key: SignedPublicKey) -> Option<SignedPublicSubKey> {
+        key.public_subkeys.into_iter().find(|subkey| {
+            subkey.signatures.last().is_some_and(|sig| {
+                let flags = sig.key_flags();
+                flags.encrypt_comms() || flags.encrypt_storage()
+            })
+        })
+    }
+
+    /// checks that the (already read) message was signed by the configured
+    /// key or one of the trusted signers present in the keyring.
+    fn verify_signature(&self, message: &Message) -> Result<(), FaError> {
+        let keys = self.read_keyring()?;
+        let mut trusted_signers = vec![self.fingerprint.clone()];
+        trusted_signers.extend(self.trusted_signers.iter().cloned());
+        let trusted_keys = keys
+            .iter()
+            .map(|key| match key {
+                PublicOrSecret::Public(k) => k.clone(),
+                PublicOrSecret::Secret(k) => k.to_public_key(),
+            })
+            .filter(|key| {
+                is_trusted_signer(
+                    &trusted_signers,
+                    &format!("{:X}", key.primary_key.fingerprint()),
+                )
+            })
+            .collect::<Vec<_>>();
+        let verifying_keys = trusted_keys
+            .iter()
+            .flat_map(|key| {
+                std::iter::once(key as &dyn VerifyingKey)
+                    .chain(key.public_subkeys.iter().map(|k| k as &dyn VerifyingKey))
+            })
+            .collect::<Vec<_>>();
+
+        let signature = match message {
+            Message::Signed { reader, .. } => reader.signature(0),
+            _ => None,
+        };
+        let Some(signature) = signature else {
+            return Err(FaError::UntrustedSignature {
+                signer: "no one".to_string(),
+            });
+        };
+        let is_trusted = message
+            .verify_nested(&verifying_keys)?
+            .iter()
+            .any(|result| matches!(result, VerificationResult::Valid(_)));
+        if !is_trusted {
+            let signer = match signature.issuer_fingerprint().first() {
+                Some(fingerprint) => format!("'{:X}'", fingerprint),
+                None => match signature.issuer_key_id().first() {
+                    Some(key_id) => format!("'{}'", key_id.to_string().to_uppercase()),
+                    None => "an unknown key".to_string(),
+                },
+            };
+            return Err(FaError::UntrustedSignature { signer: signer });
+        }
+        Ok(())
+    }
+
+    /// returns the passphrase for the secret key, prompting for it only once
+    /// and only if the key material is actually locked.
+    fn passphrase(&self, key: &SignedSecretKey) -> Result<Password, FaError> {
+        let is_locked = key.primary_key.secret_params().is_encrypted()
+            || key
+                .secret_subkeys
+                .iter()
+                .any(|subkey| subkey.key.secret_params().is_encrypted());
+        if !is_locked {
+            return Ok(Password::empty());
+        }
+        if let Some(passphrase) = self.passphrase.get() {
+            return Ok(Password::from(passphrase.as_str()));
+        }
+        let passphrase = dialoguer::Password::new()
+            .with_prompt(format!("Passphrase for key {}", &self.fingerprint))
+            .interact()?;
+        Ok(Password::from(
+            self.passphrase.get_or_init(|| passphrase).as_str(),
+        ))
+    }
+}
+
+impl CryptoBackend for NativePgp {
+    fn encrypt(&self, recipients: &[String], data: &[u8]) -> Result<Vec<u8>, FaError> {
+        let mut rng = rand::thread_rng();
+        let keys = self.read_keyring()?;
+        let secret_key = self.secret_key()?;
+        let passphrase = self.passphrase(&secret_key)?;
+
+        let mut builder = MessageBuilder::from_bytes("", data.to_vec())
+            .seipd_v1(&mut rng, SymmetricKeyAlgorithm::AES256);
+        builder.compression(CompressionAlgorithm::ZLIB).sign(
+            &secret_key.primary_key,
+            passphrase,
+            HashAlgorithm::Sha256,
+        );
+        for recipient in recipients.iter() {
+            let encryption_subkey = Self::encryption_subkey(Self::public_key(&keys, recipient)?)
+                .ok_or(FaError::InvalidFingerprint {
+                    fingerprint: recipient.clone(),
+                })?;
+            builder.encrypt_to_key(&mut rng, &encryption_subkey)?;
+        }
+        Ok(builder.to_vec(&mut rng)?)
+    }
+
+    fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, FaError> {
+        let secret_key = self.secret_key()?;
+        let passphrase = self.passphrase(&secret_key)?;
+
+        let mut message = Message::from_bytes(data)?.decrypt(&passphrase, &secret_key)?;
+        if message.is_compressed() {
+            message = message.decompress()?;
+        }
+        // the signature can only be checked once the message was read.
+        let data = message.as_data_vec()?;
+        self.verify_signature(&message)?;
+        Ok(data)
+    }
+
+    fn validate_identity(&self) -> Result<bool, FaError> {
+        match self.secret_key() {
+            Ok(key) => Ok(Self::encryption_subkey(key.to_public_key()).is_some()),
+            Err(FaError::InvalidFingerprint { .. }) | Err(FaError::NoKeyring { .. }) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn default_recipients(&self) -> Result<Vec<String>, FaError> {
+        Ok(vec![self.fingerprint.clone()])
+    }
+
+    fn format(&self) -> Format {
+        Format::OpenPgp
+    }
+
+    fn resolve_fingerprint(&self, key: &str) -> Result<Option<String>, FaError> {
+        if key.trim().is_empty() || fs::metadata(&self.keyring_path).is_err() {
+            return Ok(None);
+        }
+
+        // like 'gpg', hex strings are fingerprints/key ids and anything else
+        // is looked for in the user ids.
+        let hex = key.trim_start_matches("0x").replace(' ', "");
+        let is_key_id = hex.len() >= 8 && hex.chars().all(|c| c.is_ascii_hexdigit());
+        let user_id = key.to_lowercase();
+        let mut fingerprints = self
+            .read_keyring()?
+            .into_iter()
+            .map(|key| match key {
+                PublicOrSecret::Public(k) => k,
+                PublicOrSecret::Secret(k) => k.to_public_key(),
+            })
+            .filter(|k| match is_key_id {
+                true => Self::matches(&k.primary_key, &hex),
+                false => k.details.users.iter().any(|user| {
+                    String::from_utf8_lossy(user.id.id())
+                        .to_lowercase()
+                        .contains(&user_id)
+                }),
+            })
+            .map(|k| format!("{:X}", k.primary_key.fingerprint()))
+            .collect::<Vec<_>>();
+        fingerprints.sort();
+        fingerprints.dedup();
+        match fingerprints.as_slice() {
+            [fingerprint] => Ok(Some(fingerprint.clone())),
+            _ => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,433 @@
+use crate::{
+    error::FaError,
+    store::{Credential, Entry},
+};
+use chrono::{DateTime, Utc};
+use console::{measure_text_width, pad_str, style, truncate_str, Alignment, Term};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::borrow::Cow;
+
+/// what a password is shown as unless it is revealed.
+const PASSWORD_MASK: &str = "********";
+
+/// how the dates of a listing are shown.
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// how the timestamps of a detailed view are shown.
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S UTC";
+
+/// what every row of a table starts with.
+const ROW_PREFIX: &str = "fa | ";
+
+/// what the columns of a table are separated by.
+const COLUMN_SEPARATOR: &str = " | ";
+
+/// what a truncated value ends with.
+const ELLIPSIS: &str = "…";
+
+/// columns aren't truncated below this, even if the rows don't fit.
+const MIN_COLUMN_WIDTH: usize = 8;
+
+/// How 'fa' prints its results.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// styled, for humans.
+    #[default]
+    Table,
+    /// unstyled and tab separated, for shell scripts.
+    Plain,
+    /// a single json document, for other tools.
+    Json,
+}
+
+impl OutputFormat {
+    /// styling only ever makes sense for humans.
+    pub fn apply(&self) {
+        if *self != OutputFormat::Table {
+            console::set_colors_enabled(false);
+            console::set_colors_enabled_stderr(false);
+        }
+    }
+}
+
+/// print a status message. it goes to stderr unless the output is meant for
+/// humans, so it doesn't get mixed up with the results.
+pub fn status(format: OutputFormat, message: String) {
+    match format {
+        OutputFormat::Table => println!("{}", message),
+        _ => eprintln!("{}", message),
+    }
+}
+
+/// print a value as pretty printed json.
+pub fn print_json<T: Serialize>(value: &T) -> Result<(), FaError> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+/// print an error as a json object with its kind, so tools don't have to
+/// match on the message.
+pub fn print_json_error(error: &FaError) {
+    let value = json!({
+        "error": {
+            "kind": error.kind(),
+            "message": error.to_string(),
+        }
+    });
+    eprintln!("{}", value);
+}
+
+/// The columns of a credential listing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Column {
+    Store,
+    Id,
+    User,
+    Password,
+    Site,
+    #[serde(alias = "tag")]
+    #[value(alias = "tag")]
+    Tags,
+    Created,
+    Modified,
+    #[serde(rename = "password-changed")]
+    PasswordChanged,
+}
+
+/// the value a column is sorted by. missing values come first.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum SortKey {
+    Timestamp(Option<DateTime<Utc>>),
+    Text(Option<String>),
+}
+
+impl Column {
+    /// the columns shown when none are configured, in their order.
+    pub const DEFAULT: [Column; 5] = [
+        Column::Id,
+        Column::User,
+        Column::Password,
+        Column::Site,
+        Column::Tags,
+    ];
+
+    fn header(&self) -> &'static str {
+        match self {
+            Column::Store => "STORE",
+            Column::Id => "ID",
+            Column::User => "USER",
+            Column::Password => "PASSWORD",
+            Column::Site => "SITE",
+            Column::Tags => "TAGS",
+            Column::Created => "CREATED",
+            Column::Modified => "MODIFIED",
+            Column::PasswordChanged => "PASSWORD CHANGED",
+        }
+    }
+
+    /// the timestamp of the column, if it is one.
+    fn timestamp(&self, credential: &Credential) -> Option<Option<DateTime<Utc>>> {
+        match self {
+            Column::Created => Some(credential.created),
+            Column::Modified => Some(credential.modified),
+            Column::PasswordChanged => Some(credential.password_changed),
+            _ => None,
+        }
+    }
+
+    /// what the column is sorted by. the masked passwords would all compare
+    /// equal, so it's the real one.
+    fn sort_key(&self, entry: &Entry) -> SortKey {
+        match self.timestamp(entry.credential) {
+            Some(timestamp) => SortKey::Timestamp(timestamp),
+            None => SortKey::Text(self.value(entry, true).map(|value| value.to_lowercase())),
+        }
+    }
+
+    /// the value of the column, or None if the credential has none.
+    fn value<'a>(&self, entry: &Entry<'a>, reveal_password: bool) -> Option<Cow<'a, str>> {
+        let credential = entry.credential;
+        match self {
+            Column::Store => Some(Cow::Borrowed(entry.store)),
+            Column::Id => Some(Cow::Borrowed(&credential.id)),
+            Column::User => Some(Cow::Borrowed(&credential.user)),
+            Column::Password => match reveal_password {
+                true => Some(Cow::Borrowed(&credential.password)),
+                false => Some(Cow::Borrowed(PASSWORD_MASK)),
+            },
+            Column::Site => credential.site.as_deref().map(Cow::Borrowed),
+            Column::Tags => match credential.tags.is_empty() {
+                true => None,
+                false => Some(Cow::Owned(
+                    credential
+                        .tags
+                        .iter()
+                        .cloned()
+                        .collect::<Vec<_>>()
+                        .join(","),
+                )),
+            },
+            Column::Created | Column::Modified | Column::PasswordChanged => self
+                .timestamp(credential)
+                .flatten()
+                .map(|timestamp| Cow::Owned(timestamp.format(DATE_FORMAT).to_string())),
+        }
+    }
+}
+
+/// How a credential listing is shown.
+#[derive(Debug, Clone)]
+pub struct ListingOptions {
+    pub reveal_password: bool,
+    pub columns: Vec<Column>,
+    pub sort: Option<Column>,
+}
+
+/// print the credentials in the given format. the passwords & secret fields
+/// are masked (or left out of the json) unless they should be revealed. the columns only
+/// apply to the table & plain formats, json always has every field. the
+/// json object has the 'credentials' next to the fields of 'json_fields'.
+pub fn print_credentials(
+    entries: &[Entry],
+    options: &ListingOptions,
+    format: OutputFormat,
+    json_fields: Value,
+) -> Result<(), FaError> {
+    let mut entries = entries.to_vec();
+    if let Some(column) = options.sort {
+        entries.sort_by_cached_key(|entry| column.sort_key(entry));
+    }
+
+    match format {
+        OutputFormat::Table => print_table(&entries, options),
+        OutputFormat::Plain => {
+            for entry in entries.iter() {
+                let values = options
+                    .columns
+                    .iter()
+                    .map(|column| column.value(entry, options.reveal_password))
+                    .map(|value| value.unwrap_or_default())
+                    .collect::<Vec<_>>();
+                println!("{}", values.join("\t"));
+            }
+        }
+        OutputFormat::Json => {
+            let values = entries
+                .iter()
+                .map(|entry| credential_json(entry, options.reveal_password))
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut object = match json_fields {
+                Value::Object(fields) => fields,
+                _ => serde_json::Map::new(),
+            };
+            object.insert("credentials".to_string(), Value::Array(values));
+            print_json(&object)?;
+        }
+    }
+    Ok(())
+}
+
+/// a credential as json with its store. the password & the values of secret
+/// fields are left out unless they should be revealed.
+fn credential_json(entry: &Entry, reveal_password: bool) -> Result<Value, FaError> {
+    let mut value = serde_json::to_value(entry.credential)?;
+    if let Value::Object(fields) = &mut value {
+        if !reveal_password {
+            fields.remove("password");
+            if let Some(Value::Array(custom_fields)) = fields.get_mut("fields") {
+                custom_fields
+                    .iter_mut()
+                    .filter(|field| field["secret"] == json!(true))
+                    .filter_map(Value::as_object_mut)
+                    .for_each(|field| {
+                        field.remove("value");
+                    });
+            }
+        }
+        fields.insert("store".to_string(), json!(entry.store));
+    }
+    Ok(value)
+}
+
+/// print every field of a single credential, one per row. the password &
+/// secret fields are masked unless they should be revealed.
+pub fn print_credential(
+    entry: &Entry,
+    reveal_password: bool,
+    format: OutputFormat,
+) -> Result<(), FaError> {
+    if format == OutputFormat::Json {
+        return print_json(&credential_json(entry, reveal_password)?);
+    }
+
+    let credential = entry.credential;
+    let mask = |value: &str, secret: bool| match secret && !reveal_password {
+        true => PASSWORD_MASK.to_string(),
+        false => value.to_string(),
+    };
+    let timestamp = |timestamp: Option<DateTime<Utc>>| match timestamp {
+        Some(timestamp) => {
+            let age = match (Utc::now() - timestamp).num_days() {
+                0 => "today".to_string(),
+                1 => "1 day ago".to_string(),
+                days => format!("{} days ago", days),
+            };
+            format!("{} ({})", timestamp.format(TIMESTAMP_FORMAT), age)
+        }
+        None => "unknown".to_string(),
+    };
+    let mut rows = vec![
+        ("store".to_string(), entry.store.to_string()),
+        ("id".to_string(), credential.id.clone()),
+        ("user".to_string(), credential.user.clone()),
+        ("password".to_string(), mask(&credential.password, true)),
+        (
+            "site".to_string(),
+            credential.site.clone().unwrap_or_default(),
+        ),
+        (
+            "tags".to_string(),
+            credential
+                .tags
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(","),
+        ),
+        (
+            "notes".to_string(),
+            credential.notes.clone().unwrap_or_default(),
+        ),
+    ];
+    for field in credential.fields.iter() {
+        rows.push((field.name.clone(), mask(&field.value, field.secret)));
+    }
+    rows.push(("created".to_string(), timestamp(credential.created)));
+    rows.push(("modified".to_string(), timestamp(credential.modified)));
+    rows.push((
+        "password changed".to_string(),
+        timestamp(credential.password_changed),
+    ));
+
+    match format {
+        OutputFormat::Plain => {
+            for (name, value) in rows.iter() {
+                println!("{}\t{}", name, value);
+            }
+        }
+        _ => {
+            let width = rows
+                .iter()
+                .map(|(name, _)| measure_text_width(name))
+                .max()
+                .unwrap_or_default();
+            for (name, value) in rows.iter() {
+                let value = match value.is_empty() {
+                    true => "-",
+                    false => value.as_str(),
+                };
+                println!(
+                    "{}{}{}{}",
+                    style(ROW_PREFIX).bold().dim(),
+                    style(pad_str(name, width, Alignment::Left, None)).bold(),
+                    COLUMN_SEPARATOR,
+                    value
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// print the credentials as 'fa | id | user | password | site | tag' rows
+/// that are aligned to the widest value of every column. the widest columns
+/// are truncated until the rows fit into the terminal.
+fn print_table(entries: &[Entry], options: &ListingOptions) {
+    if entries.is_empty() || options.columns.is_empty() {
+        return;
+    }
+
+    let rows = entries
+        .iter()
+        .map(|entry| {
+            options
+                .columns
+                .iter()
+                .map(|column| match column {
+                    Column::Id => entry.credential.short_id().to_string(),
+                    _ => column
+                        .value(entry, options.reveal_password)
+                        .unwrap_or(Cow::Borrowed("-"))
+                        .to_string(),
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut widths = options
+        .columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            rows.iter()
+                .map(|row| measure_text_width(&row[i]))
+                .fold(column.header().len(), usize::max)
+        })
+        .collect::<Vec<_>>();
+
+    // the id is short already and useless once truncated.
+    let minimum_widths = options
+        .columns
+        .iter()
+        .zip(widths.iter())
+        .map(|(column, width)| match column {
+            Column::Id => *width,
+            _ => column.header().len().max(MIN_COLUMN_WIDTH).min(*width),
+        })
+        .collect::<Vec<_>>();
+    if let Some((_, terminal_width)) = Term::stdout().size_checked() {
+        let decoration_width = ROW_PREFIX.len() + COLUMN_SEPARATOR.len() * (widths.len() - 1);
+        while widths.iter().sum::<usize>() + decoration_width > terminal_width as usize {
+            let widest = widths
+                .iter()
+                .enumerate()
+                .filter(|(i, width)| **width > minimum_widths[*i])
+                .max_by_key(|(_, width)| **width)
+                .map(|(i, _)| i);
+            match widest {
+                Some(i) => widths[i] -= 1,
+                None => break,
+            }
+        }
+    }
+
+    let render = |cells: Vec<String>| {
+        cells
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| match measure_text_width(cell) > *width {
+                true => truncate_str(cell, *width, ELLIPSIS),
+                false => pad_str(cell, *width, Alignment::Left, None),
+            })
+            .collect::<Vec<_>>()
+            .join(COLUMN_SEPARATOR)
+            .trim_end()
+            .to_string()
+    };
+
+    let header = options
+        .columns
+        .iter()
+        .map(|column| column.header().to_string())
+        .collect::<Vec<_>>();
+    println!(
+        "{}{}",
+        style(ROW_PREFIX).bold().dim(),
+        style(render(header)).bold()
+    );
+    for row in rows {
+        println!("{}{}", style(ROW_PREFIX).bold().dim(), render(row));
+    }
+}
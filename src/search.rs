@@ -0,0 +1,82 @@
+use crate::{
+    error::FaError,
+    store::{Credential, Entry},
+};
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use regex::{Regex, RegexBuilder};
+
+/// How a search query is matched against the credentials.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchMode {
+    /// the characters of the query appear in order, e.g. 'gthb' in 'github.com'.
+    Fuzzy,
+    /// the query appears as is.
+    Exact,
+    /// the query is a regular expression.
+    Regex,
+}
+
+/// A compiled search query. Every mode ignores the case.
+pub enum Matcher {
+    Fuzzy(Box<SkimMatcherV2>, String),
+    Exact(String),
+    Regex(Regex),
+}
+
+impl Matcher {
+    pub fn new(query: &str, mode: SearchMode) -> Result<Self, FaError> {
+        match mode {
+            SearchMode::Fuzzy => Ok(Matcher::Fuzzy(
+                Box::new(SkimMatcherV2::default().ignore_case()),
+                query.to_string(),
+            )),
+            SearchMode::Exact => Ok(Matcher::Exact(query.to_lowercase())),
+            SearchMode::Regex => Ok(Matcher::Regex(
+                RegexBuilder::new(query).case_insensitive(true).build()?,
+            )),
+        }
+    }
+
+    /// how well the credential matches, or None if it doesn't. only the fuzzy
+    /// mode ranks the matches, the others score every match the same.
+    pub fn score(&self, credential: &Credential) -> Option<i64> {
+        let fields = [
+            Some(credential.user.as_str()),
+            credential.site.as_deref(),
+            credential.notes.as_deref(),
+        ];
+        // secret fields are left out, like the password.
+        let custom_fields = credential
+            .fields
+            .iter()
+            .filter(|field| !field.secret)
+            .map(|field| field.value.as_str());
+        fields
+            .into_iter()
+            .flatten()
+            .chain(credential.tags.iter().map(String::as_str))
+            .chain(custom_fields)
+            .filter_map(|field| self.score_field(field))
+            .max()
+    }
+
+    fn score_field(&self, field: &str) -> Option<i64> {
+        match self {
+            Matcher::Fuzzy(_, query) if query.is_empty() => Some(0),
+            Matcher::Fuzzy(matcher, query) => matcher.fuzzy_match(field, query),
+            Matcher::Exact(query) => field.to_lowercase().contains(query.as_str()).then_some(0),
+            Matcher::Regex(regex) => regex.is_match(field).then_some(0),
+        }
+    }
+}
+
+/// the entries that match, best match first. equally good matches keep
+/// their order.
+pub fn search<'a>(matcher: &Matcher, entries: &[Entry<'a>]) -> Vec<Entry<'a>> {
+    let mut matches = entries
+        .iter()
+        .filter_map(|entry| matcher.score(entry.credential).map(|score| (score, *entry)))
+        .collect::<Vec<_>>();
+    matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    matches.into_iter().map(|(_, entry)| entry).collect()
+}
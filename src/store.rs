@@ -1,20 +1,178 @@
+use chrono::{DateTime, Utc};
+use clap::crate_version;
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use uuid::Uuid;
 
-use crate::{error::FaError, gpg::Gpg};
+use crate::{
+    config::{Config, InnerConfigSecurity},
+    crypto::{self, CryptoBackend, Format},
+    error::FaError,
+};
 use std::{
-    fs::{self, File, OpenOptions},
-    io::{Read, Write},
+    collections::BTreeSet,
+    ffi::OsStr,
+    fs::{self, File, TryLockError},
+    io::Write,
     path::{Path, PathBuf},
+    rc::Rc,
+    str::FromStr,
+    thread,
+    time::{Duration, Instant},
 };
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// every store starts with a plaintext line naming the format of the
+/// ciphertext that follows, e.g. "fa:age". it lets the loader pick the right
+/// backend before decrypting. stores without it predate the header and are
+/// plain OpenPGP messages.
+const HEADER_PREFIX: &str = "fa:";
+
+/// the recipient list of a store lives next to it in '<store>.recipients',
+/// one fingerprint (or age/ssh public key) per line. without it, the store is
+/// encrypted to the configured key alone.
+const RECIPIENTS_EXTENSION: &str = "recipients";
+
+/// the previous versions of a store are kept encrypted in
+/// '<base_path>/.backups/<store>/<backup-id>.fa' where the id is the time the
+/// backup was taken, so sorting the ids sorts the backups by age.
+const BACKUPS_DIRECTORY: &str = ".backups";
+const BACKUP_ID_FORMAT: &str = "%Y%m%dT%H%M%S%.3fZ";
+
+/// how often to check whether a locked store was released.
+const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// credentials are shown with the first few characters of their id, which is
+/// usually enough to tell them apart.
+const SHORT_ID_LENGTH: usize = 8;
+
+/// the shortest id prefix that is matched against ids rather than users.
+const MIN_ID_PREFIX_LENGTH: usize = 4;
+
+/// the namespace of the ids given to credentials of stores from before ids.
+const MIGRATED_ID_NAMESPACE: Uuid = Uuid::from_u128(0x6661_0000_5f1d_4e2b_9c3a_7d1e_4f2a_8b6c);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Credential {
-    // pair of user and password are supposed to be unique across the entire store.
+    // a random uuid, stable across edits.
+    pub id: String,
+
+    // pair of user and site are supposed to be unique across the entire store.
     pub user: String,
     pub password: String,
 
     pub site: Option<String>,
-    pub tag: Option<String>,
+    pub tags: BTreeSet<String>,
+
+    pub notes: Option<String>,
+    // e.g. security questions or recovery codes, in the order they were added.
+    pub fields: Vec<CustomField>,
+
+    // None for credentials from stores that didn't record them yet.
+    pub created: Option<DateTime<Utc>>,
+    pub modified: Option<DateTime<Utc>>,
+    pub password_changed: Option<DateTime<Utc>>,
+}
+
+/// A named value of a credential, like an account number. Secret ones are
+/// masked just like the password.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomField {
+    pub name: String,
+    pub value: String,
+    pub secret: bool,
+}
+
+/// A credential along with the name of the store it is in.
+#[derive(Debug, Clone, Copy)]
+pub struct Entry<'a> {
+    pub store: &'a str,
+    pub credential: &'a Credential,
+}
+
+impl Credential {
+    pub fn new(user: &str, password: &str, site: Option<String>, tags: BTreeSet<String>) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            user: user.to_string(),
+            password: password.to_string(),
+            site: site,
+            tags: tags,
+            notes: None,
+            fields: Vec::new(),
+            created: Some(now),
+            modified: Some(now),
+            password_changed: Some(now),
+        }
+    }
+
+    /// record the time of an edit if the credential differs from what it
+    /// was before.
+    pub fn touch(&mut self, previous: &Credential) {
+        let now = Some(Utc::now());
+        if self.password != previous.password {
+            self.password_changed = now;
+        }
+        if self != previous {
+            self.modified = now;
+        }
+    }
+
+    /// the custom field with the name, ignoring the case.
+    pub fn field(&self, name: &str) -> Option<&CustomField> {
+        self.fields
+            .iter()
+            .find(|field| field.name.eq_ignore_ascii_case(name))
+    }
+
+    /// set the value of a custom field, adding it if there is none yet. an
+    /// empty value removes the field.
+    pub fn set_field(&mut self, name: &str, value: &str, secret: bool) {
+        let position = self
+            .fields
+            .iter()
+            .position(|field| field.name.eq_ignore_ascii_case(name));
+        match (position, value.is_empty()) {
+            (Some(i), true) => {
+                self.fields.remove(i);
+            }
+            (Some(i), false) => {
+                self.fields[i].value = value.to_string();
+                self.fields[i].secret = secret;
+            }
+            (None, true) => (),
+            (None, false) => self.fields.push(CustomField {
+                name: name.to_string(),
+                value: value.to_string(),
+                secret: secret,
+            }),
+        }
+    }
+
+    pub fn short_id(&self) -> &str {
+        &self.id[..SHORT_ID_LENGTH.min(self.id.len())]
+    }
+}
+
+/// the version of the envelope written by this version of 'fa'. stores of
+/// older versions are upgraded by `Store::migrate` when they are loaded and
+/// written in this version once they are saved.
+const STORE_VERSION: u64 = 5;
+
+/// what actually gets encrypted into a store file.
+#[derive(Debug, Serialize, Deserialize)]
+struct StoreEnvelope {
+    version: u64,
+    metadata: StoreMetadata,
+    credentials: Vec<Credential>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct StoreMetadata {
+    name: String,
+    // the version of 'fa' that last saved the store.
+    written_by: String,
 }
 
 #[derive(Debug, Clone)]
@@ -22,85 +180,593 @@ pub struct Store {
     pub name: String,
     pub path: String,
     pub data: Vec<Credential>,
+    pub recipients: Vec<String>,
+    backend: Rc<dyn CryptoBackend>,
+    backup_retention: usize,
+    // an exclusive lock on '.<store>.lock', held for as long as the store is
+    // around so a load-modify-save cycle can't interleave with another 'fa'.
+    _lock: Rc<File>,
 }
 
 impl Store {
-    pub fn new(name: &String, store_path: PathBuf, fingerprint: &String) -> Result<Self, FaError> {
-        if Self::check_if_exists(&store_path) {
-            return Err(FaError::AlreadyPresent { path: store_path });
-        };
+    pub fn new(
+        name: &String,
+        store_path: PathBuf,
+        configuration: &Config,
+    ) -> Result<Self, FaError> {
+        let backend = crypto::from_config(&configuration._inner.security)?;
+        Self::new_with_backend(name, store_path, backend, configuration)
+    }
 
+    /// create a store encrypted by the given backend instead of the
+    /// configured one, e.g. a fake one that doesn't need a keyring.
+    pub fn new_with_backend(
+        name: &String,
+        store_path: PathBuf,
+        backend: Box<dyn CryptoBackend>,
+        configuration: &Config,
+    ) -> Result<Self, FaError> {
         // ensure parent directory exists.
         fs::create_dir_all(store_path.parent().ok_or(FaError::UnexpectedNone)?)?;
 
-        let mut store_file = File::options()
-            .read(true)
-            .write(true)
-            .create(true)
-            .append(false)
-            .open(&store_path)?;
-
-        let encrypted_data = match Gpg::encrypt(fingerprint, &String::new()) {
-            Ok(d) => d,
-            Err(e) => {
-                fs::remove_file(&store_path)?;
-                return Err(e);
-            }
+        let lock = Self::lock(&store_path, configuration.lock_timeout())?;
+        if Self::check_if_exists(&store_path) {
+            return Err(FaError::AlreadyPresent { path: store_path });
         };
-        store_file.write_all(&encrypted_data)?;
+
+        // a store that couldn't be created doesn't need its lock file either.
+        let lock_path = Self::get_lock_file_path(&store_path)?;
+        Self::create(name, store_path, backend, configuration, lock).inspect_err(|_| {
+            let _ = fs::remove_file(&lock_path);
+        })
+    }
+
+    fn create(
+        name: &String,
+        store_path: PathBuf,
+        backend: Box<dyn CryptoBackend>,
+        configuration: &Config,
+        lock: File,
+    ) -> Result<Self, FaError> {
+        let backend: Rc<dyn CryptoBackend> = backend.into();
+        let recipients = backend.default_recipients()?;
+
         let store_path_string = store_path
             .to_str()
             .ok_or(FaError::UnexpectedNone)?
             .to_string();
-        Ok(Self {
+        let store = Self {
             name: name.to_string(),
             path: store_path_string,
             data: Vec::new(),
-        })
+            recipients: recipients,
+            backend: backend,
+            backup_retention: configuration._inner.store.backup_retention,
+            _lock: Rc::new(lock),
+        };
+
+        // encrypt before touching the disk so a failure leaves nothing behind.
+        Self::write_atomically(&store_path, &store.encrypt()?, false)?;
+        Ok(store)
     }
 
-    pub fn load(name: &String, store_path: PathBuf, fingerprint: &String) -> Result<Self, FaError> {
+    pub fn load(
+        name: &String,
+        store_path: PathBuf,
+        configuration: &Config,
+    ) -> Result<Self, FaError> {
         // check if store exists.
         if !Self::check_if_exists(&store_path) {
             return Err(FaError::NoStore { path: store_path });
         }
+        let lock = Self::lock(&store_path, configuration.lock_timeout())?;
 
-        // load store.
-        let mut file = OpenOptions::new()
-            .create(true)
-            .write(true)
-            .read(true)
-            .open(&store_path)?;
-        let mut file_contents = Vec::new();
-        file.read_to_end(&mut file_contents)?;
+        // an outdated store is only upgraded in memory, so reading it never
+        // writes. it is written in the current version the next time it is saved.
+        let file_contents = fs::read(&store_path)?;
+        let backend = Self::backend_for(&file_contents, configuration)?;
+        Self::open(
+            name,
+            store_path,
+            &file_contents,
+            backend,
+            configuration,
+            lock,
+        )
+    }
 
-        let data = Gpg::decrypt(fingerprint, file_contents)?;
+    /// replace a store with one of its backups. the current version of the
+    /// store is backed up in turn, so a restore can be undone as well.
+    pub fn restore(
+        name: &String,
+        store_path: PathBuf,
+        backup_id: &String,
+        configuration: &Config,
+    ) -> Result<Self, FaError> {
+        fs::create_dir_all(store_path.parent().ok_or(FaError::UnexpectedNone)?)?;
+        let lock = Self::lock(&store_path, configuration.lock_timeout())?;
+
+        // only ids of actual backups, so the id can't point outside of them.
+        if !Self::list_backups(&store_path)?.contains(backup_id) {
+            return Err(FaError::NoBackup {
+                store: name.to_string(),
+                id: backup_id.to_string(),
+            });
+        }
+        let backup_path =
+            Self::get_backups_directory(&store_path)?.join(format!("{}.fa", backup_id));
+
+        // only restore backups we can actually open.
+        let backup_contents = fs::read(&backup_path)?;
+        let backend = Self::backend_for(&backup_contents, configuration)?;
+        let store = Self::open(
+            name,
+            store_path,
+            &backup_contents,
+            backend,
+            configuration,
+            lock,
+        )?;
+        store.write(&backup_contents)?;
+        Ok(store)
+    }
+
+    /// the backend that can open the contents of a store file.
+    fn backend_for(
+        file_contents: &[u8],
+        configuration: &Config,
+    ) -> Result<Box<dyn CryptoBackend>, FaError> {
+        let (format, _) = Self::split_header(file_contents)?;
+        crypto::for_format(format, &configuration._inner.security)
+    }
+
+    /// decrypt & parse the contents of a store file, upgrading it if it was
+    /// written by an older version.
+    fn open(
+        name: &String,
+        store_path: PathBuf,
+        file_contents: &[u8],
+        backend: Box<dyn CryptoBackend>,
+        configuration: &Config,
+        lock: File,
+    ) -> Result<Self, FaError> {
+        // decrypt & transform data.
+        let (_, encrypted_data) = Self::split_header(file_contents)?;
+        let backend: Rc<dyn CryptoBackend> = backend.into();
+        let data = backend.decrypt(encrypted_data)?;
         let store_data = match data.is_empty() {
             true => Vec::new(),
-            false => data,
+            false => {
+                let contents = serde_json::from_slice::<Value>(&data)?;
+                let envelope = Self::migrate(contents, name)?;
+                serde_json::from_value::<StoreEnvelope>(envelope)?.credentials
+            }
+        };
+
+        let recipients = match Self::read_recipients(&store_path)? {
+            Some(r) => r,
+            None => backend.default_recipients()?,
         };
 
-        Ok(Store {
+        let store = Store {
             name: name.to_owned(),
             path: store_path
                 .to_str()
                 .ok_or(FaError::UnexpectedNone)?
                 .to_string(),
             data: store_data,
+            recipients: recipients,
+            backend: backend,
+            backup_retention: configuration._inner.store.backup_retention,
+            _lock: Rc::new(lock),
+        };
+        Ok(store)
+    }
+
+    /// the index of the single credential with the given id (or a prefix of
+    /// it) or user, among the ones passing the filter. ids take precedence
+    /// over users.
+    pub fn find(
+        &self,
+        target: &str,
+        filter: impl Fn(&Credential) -> bool,
+    ) -> Result<usize, FaError> {
+        let target_id = target.to_lowercase();
+        let matches_id = |credential: &Credential| {
+            target_id.len() >= MIN_ID_PREFIX_LENGTH && credential.id.starts_with(&target_id)
+        };
+        let is_id = self.data.iter().any(matches_id);
+        let matches = self
+            .data
+            .iter()
+            .enumerate()
+            .filter(|(_, credential)| match is_id {
+                true => matches_id(credential),
+                false => credential.user == target,
+            })
+            .filter(|(_, credential)| filter(credential))
+            .collect::<Vec<_>>();
+
+        match matches.as_slice() {
+            [] => Err(FaError::NoMatchingCredential {
+                target: target.to_string(),
+            }),
+            [(index, _)] => Ok(*index),
+            _ => Err(FaError::AmbiguousCredential {
+                target: target.to_string(),
+                ids: matches
+                    .iter()
+                    .map(|(_, credential)| credential.short_id())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            }),
+        }
+    }
+
+    /// whether another credential of the store has the same user and site.
+    pub fn is_duplicate(&self, candidate: &Credential) -> bool {
+        self.data.iter().any(|credential| {
+            credential.id != candidate.id
+                && credential.user == candidate.user
+                && credential.site == candidate.site
         })
     }
 
-    pub fn save(&self, fingerprint: &String) -> Result<(), FaError> {
-        let data_str = serde_json::to_string(&self.data)?;
-        let mut store_file = File::options()
+    pub fn save(&self) -> Result<(), FaError> {
+        let contents = self.encrypt()?;
+        self.write(&contents)
+    }
+
+    /// the full contents of the store file (header & ciphertext).
+    pub fn encrypt(&self) -> Result<Vec<u8>, FaError> {
+        let encrypted_data = self.backend.encrypt(&self.recipients, &self.serialize()?)?;
+        let mut contents = Self::header(self.backend.format());
+        contents.extend(encrypted_data);
+        Ok(contents)
+    }
+
+    /// check that the given store file contents decrypt to the credentials
+    /// of this store.
+    pub fn verify(&self, contents: &[u8]) -> Result<bool, FaError> {
+        let (_, encrypted_data) = Self::split_header(contents)?;
+        let data = self.backend.decrypt(encrypted_data)?;
+        Ok(data == self.serialize()?)
+    }
+
+    /// the plaintext of the store, i.e. the current version of the envelope.
+    fn serialize(&self) -> Result<Vec<u8>, FaError> {
+        let envelope = StoreEnvelope {
+            version: STORE_VERSION,
+            metadata: StoreMetadata {
+                name: self.name.clone(),
+                written_by: format!("fa {}", crate_version!()),
+            },
+            credentials: self.data.clone(),
+        };
+        Ok(serde_json::to_vec(&envelope)?)
+    }
+
+    fn version(contents: &Value) -> Result<u64, FaError> {
+        match contents {
+            // version 0 was a bare list of credentials.
+            Value::Array(_) => Ok(0),
+            Value::Object(envelope) => envelope
+                .get("version")
+                .and_then(Value::as_u64)
+                .ok_or(FaError::UnexpectedNone),
+            _ => Err(FaError::UnexpectedNone),
+        }
+    }
+
+    /// upgrade the decrypted contents of a store to the current version of
+    /// the envelope, one version at a time. the upgraded store is written
+    /// back the next time it is saved.
+    fn migrate(mut contents: Value, store_name: &str) -> Result<Value, FaError> {
+        loop {
+            contents = match Self::version(&contents)? {
+                STORE_VERSION => return Ok(contents),
+                0 => json!({
+                    "version": 1,
+                    "metadata": {},
+                    "credentials": contents,
+                }),
+                // version 2 gave every credential an id. they're derived from
+                // the store & the position, so they're the same every time the
+                // store is read until it is saved.
+                1 => {
+                    let mut credentials = contents["credentials"].take();
+                    for (index, credential) in credentials
+                        .as_array_mut()
+                        .ok_or(FaError::UnexpectedNone)?
+                        .iter_mut()
+                        .enumerate()
+                    {
+                        let id = Uuid::new_v5(
+                            &MIGRATED_ID_NAMESPACE,
+                            format!("{}/{}", store_name, index).as_bytes(),
+                        );
+                        credential["id"] = json!(id.to_string());
+                    }
+                    json!({
+                        "version": 2,
+                        "metadata": contents["metadata"].take(),
+                        "credentials": credentials,
+                    })
+                }
+                // version 3 turned the single tag into a set of tags.
+                2 => {
+                    let mut credentials = contents["credentials"].take();
+                    for credential in credentials.as_array_mut().ok_or(FaError::UnexpectedNone)? {
+                        let credential =
+                            credential.as_object_mut().ok_or(FaError::UnexpectedNone)?;
+                        let tags = match credential.remove("tag") {
+                            Some(Value::String(tag)) if !tag.is_empty() => json!([tag]),
+                            _ => json!([]),
+                        };
+                        credential.insert("tags".to_string(), tags);
+                    }
+                    json!({
+                        "version": 3,
+                        "metadata": contents["metadata"].take(),
+                        "credentials": credentials,
+                    })
+                }
+                // version 4 added notes and custom fields.
+                3 => {
+                    let mut credentials = contents["credentials"].take();
+                    for credential in credentials.as_array_mut().ok_or(FaError::UnexpectedNone)? {
+                        credential["notes"] = Value::Null;
+                        credential["fields"] = json!([]);
+                    }
+                    json!({
+                        "version": 4,
+                        "metadata": contents["metadata"].take(),
+                        "credentials": credentials,
+                    })
+                }
+                // version 5 added timestamps, which are unknown for older credentials.
+                4 => {
+                    let mut credentials = contents["credentials"].take();
+                    for credential in credentials.as_array_mut().ok_or(FaError::UnexpectedNone)? {
+                        credential["created"] = Value::Null;
+                        credential["modified"] = Value::Null;
+                        credential["password_changed"] = Value::Null;
+                    }
+                    json!({
+                        "version": 5,
+                        "metadata": contents["metadata"].take(),
+                        "credentials": credentials,
+                    })
+                }
+                version => return Err(FaError::UnsupportedStoreVersion { version: version }),
+            };
+        }
+    }
+
+    pub fn write(&self, contents: &[u8]) -> Result<(), FaError> {
+        self.backup()?;
+        Self::write_atomically(Path::new(&self.path), contents, true)
+    }
+
+    /// copy the store file as it is on disk to the backups, dropping the
+    /// oldest backups beyond the configured retention.
+    pub fn backup(&self) -> Result<(), FaError> {
+        let store_path = Path::new(&self.path);
+        if self.backup_retention == 0 || !Self::check_if_exists(&store_path.to_path_buf()) {
+            return Ok(());
+        }
+
+        let backups_directory = Self::get_backups_directory(store_path)?;
+        fs::create_dir_all(&backups_directory)?;
+        let backup_id = Utc::now().format(BACKUP_ID_FORMAT).to_string();
+        let backup_path = backups_directory.join(format!("{}.fa", backup_id));
+        Self::write_atomically(&backup_path, &fs::read(store_path)?, true)?;
+
+        let backup_ids = Self::list_backups(store_path)?;
+        let expired = backup_ids.len().saturating_sub(self.backup_retention);
+        for backup_id in backup_ids.iter().take(expired) {
+            fs::remove_file(backups_directory.join(format!("{}.fa", backup_id)))?;
+        }
+        Ok(())
+    }
+
+    /// ids of all the backups of a store, oldest first.
+    pub fn list_backups(store_path: &Path) -> Result<Vec<String>, FaError> {
+        let backups_directory = Self::get_backups_directory(store_path)?;
+        if !Self::check_if_exists(&backups_directory) {
+            return Ok(Vec::new());
+        }
+        let mut backup_ids = Vec::new();
+        for entry in fs::read_dir(backups_directory)?.flatten() {
+            let file_path = entry.path();
+            if file_path.extension().and_then(OsStr::to_str) == Some("fa") {
+                let backup_id = file_path
+                    .file_stem()
+                    .and_then(OsStr::to_str)
+                    .ok_or(FaError::UnexpectedNone)?;
+                backup_ids.push(backup_id.to_string());
+            }
+        }
+        backup_ids.sort();
+        Ok(backup_ids)
+    }
+
+    fn get_backups_directory(store_path: &Path) -> Result<PathBuf, FaError> {
+        let store_name = store_path
+            .file_stem()
+            .and_then(OsStr::to_str)
+            .ok_or(FaError::UnexpectedNone)?;
+        Ok(store_path
+            .with_file_name(BACKUPS_DIRECTORY)
+            .join(store_name))
+    }
+
+    /// delete the store along with its recipient list & lock file. the
+    /// backups are kept.
+    pub fn delete(self) -> Result<(), FaError> {
+        let store_path = PathBuf::from(&self.path);
+        fs::remove_file(&store_path)?;
+        let recipients_path = Self::get_recipients_file_path(&store_path);
+        if Self::check_if_exists(&recipients_path) {
+            fs::remove_file(&recipients_path)?;
+        }
+        fs::remove_file(Self::get_lock_file_path(&store_path)?)?;
+        Ok(())
+    }
+
+    /// write to a temporary file next to the target, flush it to disk and
+    /// rename it over the target. a crash or an error at any point leaves
+    /// either the old or the new file, but never a partial one.
+    pub fn write_atomically(path: &Path, contents: &[u8], overwrite: bool) -> Result<(), FaError> {
+        let directory = path.parent().ok_or(FaError::UnexpectedNone)?;
+        let mut temp_file = tempfile::Builder::new()
+            .prefix(".fa-")
+            .suffix(".tmp")
+            .tempfile_in(directory)?;
+        temp_file.write_all(contents)?;
+        temp_file.as_file().sync_all()?;
+        match overwrite {
+            true => temp_file.persist(path).map_err(|e| e.error)?,
+            false => temp_file.persist_noclobber(path).map_err(|e| e.error)?,
+        };
+
+        // the rename itself only hits the disk once the directory is synced.
+        #[cfg(unix)]
+        File::open(directory)?.sync_all()?;
+        Ok(())
+    }
+
+    /// switch the store over to another key, e.g. when rotating keys. the
+    /// store has to be saved for it to be encrypted to that key.
+    pub fn use_security(&mut self, security: &InnerConfigSecurity) -> Result<(), FaError> {
+        self.backend = crypto::for_format(self.backend.format(), security)?.into();
+        Ok(())
+    }
+
+    pub fn format(&self) -> Format {
+        self.backend.format()
+    }
+
+    /// whether the recipient is the configured key, which has to stay a
+    /// recipient to open the store.
+    pub fn is_own_recipient(&self, recipient: &str) -> Result<bool, FaError> {
+        Ok(self
+            .backend
+            .default_recipients()?
+            .iter()
+            .any(|own| crypto::is_same_key(own, recipient)))
+    }
+
+    /// persist the recipient list. the store itself has to be saved as well
+    /// for it to be encrypted to the new recipients.
+    pub fn save_recipients(&self) -> Result<(), FaError> {
+        let recipients_path = Self::get_recipients_file_path(Path::new(&self.path));
+        let recipients = self
+            .recipients
+            .iter()
+            .map(|recipient| format!("{}\n", recipient))
+            .collect::<String>();
+        Self::write_atomically(&recipients_path, recipients.as_bytes(), true)
+    }
+
+    /// the recipients listed next to a store, if there are any.
+    pub fn read_recipients(store_path: &Path) -> Result<Option<Vec<String>>, FaError> {
+        let recipients_path = Self::get_recipients_file_path(store_path);
+        if !Self::check_if_exists(&recipients_path) {
+            return Ok(None);
+        }
+        let recipients = fs::read_to_string(recipients_path)?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(String::from)
+            .collect::<Vec<_>>();
+        match recipients.is_empty() {
+            true => Ok(None),
+            false => Ok(Some(recipients)),
+        }
+    }
+
+    /// take the exclusive lock of a store, waiting for up to the given
+    /// timeout if another 'fa' is holding it. the lock is released when the
+    /// returned file is closed.
+    fn lock(store_path: &Path, timeout: Duration) -> Result<File, FaError> {
+        let lock_file = File::options()
             .write(true)
             .create(true)
-            .truncate(true)
-            .append(false)
-            .open(&self.path)?;
-        let encrypted_data = Gpg::encrypt(fingerprint, &data_str)?;
-        store_file.write_all(&encrypted_data)?;
-        Ok(())
+            .truncate(false)
+            .open(Self::get_lock_file_path(store_path)?)?;
+        let started_at = Instant::now();
+        loop {
+            match lock_file.try_lock() {
+                Ok(()) => return Ok(lock_file),
+                Err(TryLockError::WouldBlock) if started_at.elapsed() < timeout => {
+                    thread::sleep(LOCK_POLL_INTERVAL)
+                }
+                Err(TryLockError::WouldBlock) => {
+                    return Err(FaError::StoreLocked {
+                        path: store_path.to_path_buf(),
+                        timeout: timeout.as_secs(),
+                    })
+                }
+                Err(TryLockError::Error(e)) => return Err(e.into()),
+            }
+        }
+    }
+
+    fn get_lock_file_path(store_path: &Path) -> Result<PathBuf, FaError> {
+        let store_name = store_path
+            .file_stem()
+            .and_then(OsStr::to_str)
+            .ok_or(FaError::UnexpectedNone)?;
+        Ok(store_path.with_file_name(format!(".{}.lock", store_name)))
+    }
+
+    pub fn get_recipients_file_path(store_path: &Path) -> PathBuf {
+        store_path.with_extension(RECIPIENTS_EXTENSION)
+    }
+
+    fn header(format: Format) -> Vec<u8> {
+        format!("{}{}\n", HEADER_PREFIX, format).into_bytes()
+    }
+
+    /// the format of a store, as told by its header.
+    pub fn read_format(store_path: &Path) -> Result<Format, FaError> {
+        let (format, _) = Self::split_header(&fs::read(store_path)?)?;
+        Ok(format)
+    }
+
+    fn split_header(contents: &[u8]) -> Result<(Format, &[u8]), FaError> {
+        if !contents.starts_with(HEADER_PREFIX.as_bytes()) {
+            return Ok((Format::OpenPgp, contents));
+        }
+        let line_end = contents
+            .iter()
+            .position(|b| *b == b'\n')
+            .ok_or(FaError::UnexpectedNone)?;
+        let format = std::str::from_utf8(&contents[HEADER_PREFIX.len()..line_end])
+            .map_err(|_| FaError::UnexpectedNone)?;
+        Ok((Format::from_str(format)?, &contents[line_end + 1..]))
+    }
+
+    /// names of all the stores in the given directory.
+    pub fn list(base_path: &String) -> Result<Vec<String>, FaError> {
+        let mut store_names = Vec::new();
+        for entry in fs::read_dir(base_path)?.flatten() {
+            if !entry.file_type().is_ok_and(|file_type| file_type.is_file()) {
+                continue;
+            }
+            let file_path = entry.path();
+            if file_path.extension().and_then(OsStr::to_str) == Some("fa") {
+                let file_name = file_path
+                    .file_stem()
+                    .and_then(OsStr::to_str)
+                    .ok_or(FaError::UnexpectedNone)?;
+                store_names.push(file_name.to_string());
+            }
+        }
+        store_names.sort();
+        Ok(store_names)
     }
 
     pub fn get_file_path(store_name: &String, base_path: &String) -> Result<PathBuf, FaError> {
@@ -114,3 +780,139 @@ impl Store {
         fs::metadata(store_path).is_ok()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::InnerConfig;
+    use tempfile::TempDir;
+
+    /// a backend that only reverses the bytes, so the store logic can be
+    /// tested without a keyring.
+    #[derive(Debug)]
+    struct FakeBackend;
+
+    impl CryptoBackend for FakeBackend {
+        fn encrypt(&self, _recipients: &[String], data: &[u8]) -> Result<Vec<u8>, FaError> {
+            Ok(data.iter().rev().copied().collect())
+        }
+
+        fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, FaError> {
+            Ok(data.iter().rev().copied().collect())
+        }
+
+        fn validate_identity(&self) -> Result<bool, FaError> {
+            Ok(true)
+        }
+
+        fn default_recipients(&self) -> Result<Vec<String>, FaError> {
+            Ok(vec!["fake".to_string()])
+        }
+
+        fn format(&self) -> Format {
+            Format::OpenPgp
+        }
+
+        fn resolve_fingerprint(&self, _key: &str) -> Result<Option<String>, FaError> {
+            Ok(None)
+        }
+    }
+
+    fn configuration(directory: &TempDir) -> Config {
+        let inner = format!(
+            "[store]\nbase_path = {:?}\ndefault_store = \"main\"\n\n[security]\n",
+            directory.path()
+        );
+        Config {
+            config_file_path: String::new(),
+            _inner: toml::from_str::<InnerConfig>(&inner).unwrap(),
+        }
+    }
+
+    /// open the store file with the fake backend.
+    fn open(configuration: &Config, store_path: &Path) -> Store {
+        let lock = Store::lock(store_path, configuration.lock_timeout()).unwrap();
+        let contents = fs::read(store_path).unwrap();
+        Store::open(
+            &"main".to_string(),
+            store_path.to_path_buf(),
+            &contents,
+            Box::new(FakeBackend),
+            configuration,
+            lock,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn saved_credentials_open_again() {
+        let directory = TempDir::new().unwrap();
+        let configuration = configuration(&directory);
+        let store_path = directory.path().join("main.fa");
+
+        let mut store = Store::new_with_backend(
+            &"main".to_string(),
+            store_path.clone(),
+            Box::new(FakeBackend),
+            &configuration,
+        )
+        .unwrap();
+        let credential = Credential::new("alice", "hunter2", None, BTreeSet::new());
+        store.data.push(credential.clone());
+        store.save().unwrap();
+        drop(store);
+
+        assert!(fs::read(&store_path).unwrap().starts_with(b"fa:openpgp\n"));
+        let store = open(&configuration, &store_path);
+        assert_eq!(store.data, [credential]);
+        assert_eq!(store.recipients, ["fake"]);
+    }
+
+    #[test]
+    fn old_stores_get_the_same_ids_every_time() {
+        let directory = TempDir::new().unwrap();
+        let configuration = configuration(&directory);
+        let store_path = directory.path().join("main.fa");
+
+        // version 0 was a bare list of credentials.
+        let plaintext = br#"[{"user":"alice","password":"hunter2","site":null,"tag":"work"}]"#;
+        fs::write(&store_path, FakeBackend.encrypt(&[], plaintext).unwrap()).unwrap();
+
+        let first = open(&configuration, &store_path).data;
+        let second = open(&configuration, &store_path).data;
+        assert_eq!(first, second);
+        assert_eq!(first[0].user, "alice");
+        assert_eq!(first[0].tags, BTreeSet::from(["work".to_string()]));
+        assert_eq!(first[0].created, None);
+    }
+
+    #[test]
+    fn finds_credentials_by_id_prefix_or_user() {
+        let directory = TempDir::new().unwrap();
+        let configuration = configuration(&directory);
+        let mut store = Store::new_with_backend(
+            &"main".to_string(),
+            directory.path().join("main.fa"),
+            Box::new(FakeBackend),
+            &configuration,
+        )
+        .unwrap();
+        store.data = vec![
+            Credential::new("alice", "hunter2", None, BTreeSet::new()),
+            Credential::new(
+                "alice",
+                "swordfish",
+                Some("example.com".to_string()),
+                BTreeSet::new(),
+            ),
+        ];
+
+        let short_id = store.data[1].short_id().to_string();
+        assert_eq!(store.find(&short_id, |_| true).unwrap(), 1);
+        assert_eq!(store.find("alice", |cred| cred.site.is_none()).unwrap(), 0);
+        assert!(matches!(
+            store.find("alice", |_| true),
+            Err(FaError::AmbiguousCredential { .. })
+        ));
+    }
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use age::secrecy::ExposeSecret;
+use common::{stderr, stdout, TestEnv};
+use std::{fs, io::Read, path::PathBuf, process::Command};
+
+/// run 'fa init' with the age backend and a freshly generated x25519
+/// identity. returns the identity.
+fn init_age(env: &TestEnv) -> age::x25519::Identity {
+    let identity = age::x25519::Identity::generate();
+    let identity_path = identity_path(env);
+    fs::write(
+        &identity_path,
+        format!("{}\n", identity.to_string().expose_secret()),
+    )
+    .unwrap();
+
+    let output = env.fa(&[
+        "init",
+        "--backend",
+        "age",
+        "--age-identity",
+        identity_path.to_str().unwrap(),
+        "--store",
+        "main",
+        "--store-path",
+        env.store_dir().to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    identity
+}
+
+fn identity_path(env: &TestEnv) -> PathBuf {
+    env.home.path().join("identity.txt")
+}
+
+fn list(env: &TestEnv) -> String {
+    let output = env.fa(&[
+        "list",
+        "--reveal",
+        "--format",
+        "plain",
+        "--columns",
+        "user,password",
+    ]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    stdout(&output)
+}
+
+#[test]
+fn round_trips_a_store() {
+    let env = TestEnv::without_init();
+    let identity = init_age(&env);
+    let output = env.fa(&["add", "meow@example.com", "hunter2"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(list(&env), "meow@example.com\thunter2\n");
+
+    // strip the "fa:age" header line, the rest is a plain age file.
+    let contents = fs::read(env.store_path("main")).unwrap();
+    assert!(contents.starts_with(b"fa:age\n"));
+    let encrypted = &contents[b"fa:age\n".len()..];
+    let mut decrypted = String::new();
+    age::Decryptor::new(encrypted)
+        .unwrap()
+        .decrypt(std::iter::once(&identity as &dyn age::Identity))
+        .unwrap()
+        .read_to_string(&mut decrypted)
+        .unwrap();
+    assert!(decrypted.contains("hunter2"), "{}", decrypted);
+}
+
+#[test]
+fn the_header_picks_the_backend() {
+    let env = TestEnv::without_init();
+    init_age(&env);
+    let output = env.fa(&["add", "meow@example.com", "hunter2"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    // an age store still opens once the configured backend is gpg.
+    env.edit_config("backend = \"age\"", "backend = \"gpg\"");
+    assert_eq!(list(&env), "meow@example.com\thunter2\n");
+}
+
+#[test]
+fn needs_the_identity() {
+    let env = TestEnv::without_init();
+    init_age(&env);
+    let output = env.fa(&["add", "meow@example.com", "hunter2"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    // a different identity can't decrypt the store.
+    let other = age::x25519::Identity::generate();
+    fs::write(
+        identity_path(&env),
+        format!("{}\n", other.to_string().expose_secret()),
+    )
+    .unwrap();
+    let output = env.fa(&["list"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn ssh_identities_need_a_recipient() {
+    let env = TestEnv::without_init();
+    let key_path = env.home.path().join("id_ed25519");
+    let output = Command::new("ssh-keygen")
+        .args(["-q", "-t", "ed25519", "-N", "", "-f"])
+        .arg(&key_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+    let store_dir = env.store_dir();
+    let init = |extra_args: &[&str]| {
+        let mut args = vec![
+            "init",
+            "--backend",
+            "age",
+            "--age-identity",
+            key_path.to_str().unwrap(),
+            "--store",
+            "main",
+            "--store-path",
+            store_dir.to_str().unwrap(),
+        ];
+        args.extend_from_slice(extra_args);
+        env.fa(&args)
+    };
+
+    // the public key of an ssh key can't be told from the private key.
+    let output = init(&[]);
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("--age-recipient"),
+        "{}",
+        stderr(&output)
+    );
+    assert!(!env.config_path().exists());
+
+    let public_key = fs::read_to_string(key_path.with_extension("pub")).unwrap();
+    let output = init(&["--age-recipient", public_key.trim()]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let output = env.fa(&["add", "meow@example.com", "hunter2"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(list(&env), "meow@example.com\thunter2\n");
+}
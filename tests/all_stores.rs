@@ -0,0 +1,53 @@
+mod common;
+
+use common::{stderr, stdout, TestEnv};
+use serde_json::Value;
+use std::fs;
+
+fn add(env: &TestEnv, user: &str, site: &str, store: &str) {
+    let output = env.fa(&["add", user, "hunter2", "--site", site, "--store", store]);
+    assert!(output.status.success(), "{}", stderr(&output));
+}
+
+#[test]
+fn search_finds_credentials_in_every_store() {
+    let env = TestEnv::new();
+    add(&env, "alice@gmail.com", "github.com", "main");
+    add(&env, "ops@corp.com", "github.com", "infra");
+    add(&env, "bob@gmail.com", "example.org", "personal");
+
+    let output = env.fa(&[
+        "search",
+        "github",
+        "--all-stores",
+        "--format",
+        "plain",
+        "--columns",
+        "store,user",
+        "--sort",
+        "store",
+    ]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        "infra\tops@corp.com\nmain\talice@gmail.com\n"
+    );
+}
+
+#[test]
+fn stores_that_fail_to_open_are_reported_and_skipped() {
+    let env = TestEnv::new();
+    add(&env, "alice@gmail.com", "github.com", "main");
+    fs::write(env.store_path("broken"), "fa:openpgp\nnot a store").unwrap();
+
+    let output = env.fa(&["search", "github", "--all-stores"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("alice@gmail.com"));
+    assert!(stderr(&output).contains("broken"), "{}", stderr(&output));
+
+    let output = env.fa(&["search", "github", "--all-stores", "--format", "json"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let value: Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(value["credentials"][0]["store"], "main");
+    assert_eq!(value["failed_stores"][0]["store"], "broken");
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use common::{stderr, stdout, TestEnv};
+use std::fs;
+
+/// every file in the store directory except for the backups, sorted.
+fn store_dir_entries(env: &TestEnv) -> Vec<String> {
+    let mut entries = fs::read_dir(env.store_dir())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .filter(|name| name != ".backups")
+        .collect::<Vec<_>>();
+    entries.sort();
+    entries
+}
+
+#[test]
+fn failed_save_keeps_the_original_store() {
+    let env = TestEnv::new();
+    assert!(env.fa(&["add", "alice", "hunter2"]).status.success());
+    let original = fs::read(env.store_path("main")).unwrap();
+
+    // encrypting to a key that doesn't exist makes gpg fail.
+    let recipients_path = env.store_dir().join("main.recipients");
+    fs::write(&recipients_path, "DEADBEEFDEADBEEF\n").unwrap();
+    let output = env.fa(&["add", "bob", "correct-horse"]);
+    assert!(!output.status.success());
+
+    assert_eq!(fs::read(env.store_path("main")).unwrap(), original);
+    assert_eq!(
+        store_dir_entries(&env),
+        [".main.lock", "main.fa", "main.recipients"]
+    );
+
+    fs::remove_file(&recipients_path).unwrap();
+    let output = env.fa(&["list"]);
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("alice"));
+    assert!(!stdout(&output).contains("bob"));
+}
+
+#[test]
+fn failed_store_creation_leaves_no_file() {
+    let env = TestEnv::new();
+    env.edit_config(
+        &format!("gpg_fingerprint = \"{}\"", env.fingerprint),
+        "gpg_fingerprint = \"DEADBEEFDEADBEEF\"",
+    );
+
+    let output = env.fa(&["store", "add", "other"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("encrypt"), "{}", stderr(&output));
+    assert!(store_dir_entries(&env).is_empty());
+}
+
+#[test]
+fn save_replaces_the_store() {
+    let env = TestEnv::new();
+    assert!(env.fa(&["add", "alice", "hunter2"]).status.success());
+    assert!(env.fa(&["add", "bob", "correct-horse"]).status.success());
+    assert_eq!(store_dir_entries(&env), [".main.lock", "main.fa"]);
+
+    let output = env.fa(&["list"]);
+    assert!(stdout(&output).contains("alice"));
+    assert!(stdout(&output).contains("bob"));
+}
+
+#[test]
+fn removed_stores_leave_nothing_behind() {
+    let env = TestEnv::new();
+    assert!(env.fa(&["store", "add", "other"]).status.success());
+    let output = env.fa(&["store", "remove", "other"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(store_dir_entries(&env).is_empty());
+}
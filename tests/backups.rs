@@ -0,0 +1,69 @@
+mod common;
+
+use common::{fa_ok, stderr, TestEnv};
+use std::fs;
+
+/// the backup ids of the 'main' store, newest first.
+fn backups(env: &TestEnv) -> Vec<String> {
+    fa_ok(env, &["store", "backups", "main"])
+        .lines()
+        .skip(1)
+        .map(|line| line.trim_start_matches("fa | ").to_string())
+        .collect()
+}
+
+fn users(env: &TestEnv) -> String {
+    fa_ok(env, &["list", "--format", "plain", "--columns", "user"])
+}
+
+#[test]
+fn backups_are_taken_and_pruned() {
+    let env = TestEnv::new();
+    env.edit_config("backup_retention = 10", "backup_retention = 2");
+    assert!(backups(&env).is_empty());
+
+    fa_ok(&env, &["add", "alice", "hunter2"]);
+    assert_eq!(backups(&env).len(), 1);
+    for user in ["bob", "carol", "dave"] {
+        fa_ok(&env, &["add", user, "hunter2"]);
+    }
+    let ids = backups(&env);
+    assert_eq!(ids.len(), 2);
+    assert!(ids[0] > ids[1]);
+}
+
+#[test]
+fn stores_can_be_restored() {
+    let env = TestEnv::new();
+    fa_ok(&env, &["add", "alice", "hunter2"]);
+    fa_ok(&env, &["add", "bob", "hunter2"]);
+
+    // the newest backup is from before bob was added.
+    let newest = backups(&env)[0].clone();
+    fa_ok(&env, &["store", "restore", "main", &newest]);
+    assert_eq!(users(&env), "alice\n");
+
+    // the restore backed up the store with bob in turn.
+    let undo = backups(&env)[0].clone();
+    fa_ok(&env, &["store", "restore", "main", &undo]);
+    assert_eq!(users(&env), "alice\nbob\n");
+}
+
+#[test]
+fn only_backups_can_be_restored() {
+    let env = TestEnv::new();
+    fa_ok(&env, &["add", "alice", "hunter2"]);
+    fa_ok(&env, &["store", "add", "other"]);
+    fa_ok(&env, &["add", "bob", "hunter2", "--store", "other"]);
+
+    // '.backups/main/../../other' is a readable store, but not a backup.
+    let before = fs::read(env.store_path("main")).unwrap();
+    let output = env.fa(&["store", "restore", "main", "../../other"]);
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("Could not find a backup"),
+        "{}",
+        stderr(&output)
+    );
+    assert_eq!(fs::read(env.store_path("main")).unwrap(), before);
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use common::{stderr, stdout, TestEnv};
+use std::{fs, thread, time::Duration};
+
+fn osc52(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", STANDARD.encode(text))
+}
+
+#[test]
+fn copy_writes_an_osc52_sequence() {
+    let env = TestEnv::new();
+    env.edit_config("method = \"auto\"", "method = \"osc52\"");
+    env.edit_config("timeout = 45", "timeout = 0");
+    assert!(env.fa(&["add", "alice", "hunter2"]).status.success());
+
+    let output = env.fa(&["copy", "alice"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).starts_with(&osc52("hunter2")));
+    assert!(!stdout(&output).contains("hunter2"));
+}
+
+#[test]
+fn copy_runs_the_copy_command_and_clears_it_later() {
+    let env = TestEnv::new();
+    let clipboard_path = env.home.path().join("clipboard");
+    env.edit_config(
+        "copy_command = []",
+        &format!(
+            "copy_command = [\"sh\", \"-c\", \"cat > {}\"]",
+            clipboard_path.display()
+        ),
+    );
+    assert!(env.fa(&["add", "alice", "hunter2"]).status.success());
+
+    let output = env.fa(&["copy", "alice", "--timeout", "1"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(!stdout(&output).contains("hunter2"));
+    assert_eq!(fs::read_to_string(&clipboard_path).unwrap(), "hunter2");
+
+    // the detached child empties it after the timeout.
+    for _ in 0..50 {
+        if fs::read_to_string(&clipboard_path).unwrap().is_empty() {
+            return;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    panic!("the clipboard was never cleared");
+}
+
+#[test]
+fn the_clear_leaves_a_newer_copy_alone() {
+    let env = TestEnv::new();
+    let clipboard_path = env.home.path().join("clipboard");
+    env.edit_config(
+        "copy_command = []",
+        &format!(
+            "copy_command = [\"sh\", \"-c\", \"cat > {}\"]",
+            clipboard_path.display()
+        ),
+    );
+    env.edit_config(
+        "paste_command = []",
+        &format!(
+            "paste_command = [\"cat\", \"{}\"]",
+            clipboard_path.display()
+        ),
+    );
+    assert!(env.fa(&["add", "alice", "hunter2"]).status.success());
+
+    let output = env.fa(&["copy", "alice", "--timeout", "1"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    fs::write(&clipboard_path, "something else").unwrap();
+
+    thread::sleep(Duration::from_secs(3));
+    assert_eq!(
+        fs::read_to_string(&clipboard_path).unwrap(),
+        "something else"
+    );
+}
+
+#[test]
+fn copy_without_a_clipboard_fails() {
+    let env = TestEnv::new();
+    assert!(env.fa(&["add", "alice", "hunter2"]).status.success());
+
+    // stdout isn't a terminal and no copy command is configured.
+    let output = env.fa(&["copy", "alice"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("clipboard"), "{}", stderr(&output));
+    assert!(!stdout(&output).contains("hunter2"));
+}
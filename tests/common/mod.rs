@@ -0,0 +1,199 @@
+//! Helpers shared by the integration tests. Every test gets its own `$HOME`
+//! and `$GNUPGHOME` with a throwaway, passphrase-less gpg key so nothing on
+//! the host is touched.
+
+#![allow(dead_code)]
+
+use std::{
+    fs,
+    path::PathBuf,
+    process::{Command, Output, Stdio},
+};
+use tempfile::TempDir;
+
+pub struct TestEnv {
+    pub home: TempDir,
+    pub gnupg_home: TempDir,
+    pub fingerprint: String,
+}
+
+impl TestEnv {
+    /// create a fresh environment with a generated key and run 'fa init'.
+    pub fn new() -> Self {
+        Self::without_init().init()
+    }
+
+    /// create a fresh environment with the key the fixture stores in
+    /// 'tests/fixtures' are encrypted to and run 'fa init'.
+    pub fn with_fixture_key() -> Self {
+        let home = TempDir::new().unwrap();
+        let gnupg_home = TempDir::new().unwrap();
+        let key_path = fixture_path("key.asc");
+        let output = gpg(&gnupg_home, &["--import", key_path.to_str().unwrap()]);
+        assert!(output.status.success(), "{}", stderr(&output));
+        Self {
+            home,
+            gnupg_home,
+            fingerprint: FIXTURE_FINGERPRINT.to_string(),
+        }
+        .init()
+    }
+
+    fn init(self) -> Self {
+        let output = self.fa(&[
+            "init",
+            "--key-fingerprint",
+            &self.fingerprint,
+            "--store",
+            "main",
+            "--store-path",
+            self.store_dir().to_str().unwrap(),
+        ]);
+        assert!(output.status.success(), "{}", stderr(&output));
+        self
+    }
+
+    /// create a fresh environment with a generated key but no configuration.
+    pub fn without_init() -> Self {
+        let home = TempDir::new().unwrap();
+        let gnupg_home = TempDir::new().unwrap();
+        let fingerprint = generate_key(&gnupg_home, "fa test <fa@test.invalid>");
+        Self {
+            home,
+            gnupg_home,
+            fingerprint,
+        }
+    }
+
+    pub fn fa(&self, args: &[&str]) -> Output {
+        self.fa_command(args).output().unwrap()
+    }
+
+    pub fn fa_command(&self, args: &[&str]) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_fa"));
+        command
+            .args(args)
+            .env("HOME", self.home.path())
+            .env("GNUPGHOME", self.gnupg_home.path())
+            .stdin(Stdio::null());
+        command
+    }
+
+    pub fn gpg(&self, args: &[&str]) -> Output {
+        gpg(&self.gnupg_home, args)
+    }
+
+    pub fn store_dir(&self) -> PathBuf {
+        self.home.path().join("stores")
+    }
+
+    pub fn store_path(&self, name: &str) -> PathBuf {
+        self.store_dir().join(format!("{}.fa", name))
+    }
+
+    pub fn config_path(&self) -> PathBuf {
+        self.home.path().join(".config/fa/config.toml")
+    }
+
+    /// switch the configuration to the native backend and hide the gpg
+    /// keyring, so a passing test proves 'gpg' was never needed. the native
+    /// keyring gets the secret key & every public key of the gpg keyring.
+    /// returns the hidden keyring directory.
+    pub fn use_native_backend(&mut self) -> TempDir {
+        // binary keys can simply be concatenated, armored ones can't.
+        let output = self.gpg(&["--export-secret-keys", &self.fingerprint]);
+        assert!(output.status.success(), "{}", stderr(&output));
+        let mut keyring = output.stdout;
+        let output = self.gpg(&["--export"]);
+        assert!(output.status.success(), "{}", stderr(&output));
+        keyring.extend(output.stdout);
+        fs::write(self.home.path().join(".config/fa/keyring.asc"), keyring).unwrap();
+
+        self.edit_config("backend = \"gpg\"", "backend = \"native\"");
+        std::mem::replace(&mut self.gnupg_home, TempDir::new().unwrap())
+    }
+
+    /// replace a line of the configuration file.
+    pub fn edit_config(&self, from: &str, to: &str) {
+        let config = fs::read_to_string(self.config_path()).unwrap();
+        assert!(config.contains(from), "{}", config);
+        fs::write(self.config_path(), config.replace(from, to)).unwrap();
+    }
+}
+
+/// the fingerprint of 'tests/fixtures/key.asc'.
+pub const FIXTURE_FINGERPRINT: &str = "9BF541BC16FE4F564F7AE6C42731B463736B7FA0";
+
+pub fn fixture_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+pub fn gpg(gnupg_home: &TempDir, args: &[&str]) -> Output {
+    Command::new("gpg")
+        .args(["--batch", "--yes", "--quiet"])
+        .args(args)
+        .env("GNUPGHOME", gnupg_home.path())
+        .output()
+        .unwrap()
+}
+
+/// generate a passphrase-less key and return its fingerprint.
+pub fn generate_key(gnupg_home: &TempDir, user_id: &str) -> String {
+    let output = gpg(
+        gnupg_home,
+        &[
+            "--passphrase",
+            "",
+            "--quick-gen-key",
+            user_id,
+            "future-default",
+            "default",
+            "never",
+        ],
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let output = gpg(gnupg_home, &["--with-colons", "--list-keys", user_id]);
+    stdout(&output)
+        .lines()
+        .find_map(|line| line.strip_prefix("fpr:::::::::"))
+        .and_then(|line| line.split(':').next())
+        .unwrap()
+        .to_string()
+}
+
+/// run fa, assert it succeeded and return its stdout.
+pub fn fa_ok(env: &TestEnv, args: &[&str]) -> String {
+    let output = env.fa(args);
+    assert!(output.status.success(), "{}", stderr(&output));
+    stdout(&output)
+}
+
+/// an environment with a store of a few credentials on different sites and
+/// tags.
+pub fn env_with_credentials() -> TestEnv {
+    let env = TestEnv::new();
+    for (user, site, tag) in [
+        ("alice@gmail.com", "github.com", Some("work")),
+        ("ci-bot@corp.com", "github.com", Some("old")),
+        ("bob@corp.com", "gitlab.com", Some("work")),
+        ("carol@example.com", "example.org", None),
+    ] {
+        let mut args = vec!["add", user, "hunter2", "--site", site];
+        if let Some(tag) = tag {
+            args.extend_from_slice(&["--tag", tag]);
+        }
+        fa_ok(&env, &args);
+    }
+    env
+}
+
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+pub fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).to_string()
+}
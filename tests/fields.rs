@@ -0,0 +1,104 @@
+mod common;
+
+use common::{fa_ok, stderr, TestEnv};
+use serde_json::Value;
+
+fn add_alice(env: &TestEnv) {
+    fa_ok(
+        env,
+        &[
+            "add",
+            "alice",
+            "hunter2",
+            "--note",
+            "recovery codes are in the safe",
+            "--field",
+            "account=1234",
+            "--secret-field",
+            "pin=0000",
+        ],
+    );
+}
+
+#[test]
+fn notes_and_custom_fields_can_be_read() {
+    let env = TestEnv::new();
+    add_alice(&env);
+
+    assert_eq!(
+        fa_ok(&env, &["get", "alice", "--field", "notes"]),
+        "recovery codes are in the safe\n"
+    );
+    assert_eq!(
+        fa_ok(&env, &["get", "alice", "--field", "account"]),
+        "1234\n"
+    );
+    assert_eq!(fa_ok(&env, &["get", "alice", "--field", "PIN"]), "0000\n");
+
+    let output = env.fa(&["get", "alice", "--field", "question"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("question"), "{}", stderr(&output));
+
+    // the notes are searched, but not the secret fields.
+    let search = |query: &str| fa_ok(&env, &["search", query, "--exact", "--format", "plain"]);
+    assert!(search("in the safe").contains("alice"));
+    assert!(search("0000").is_empty());
+}
+
+#[test]
+fn secret_fields_are_masked() {
+    let env = TestEnv::new();
+    add_alice(&env);
+
+    let listed: Value = serde_json::from_str(&fa_ok(&env, &["list", "--format", "json"])).unwrap();
+    let fields = &listed["credentials"][0]["fields"];
+    assert_eq!(fields[0]["value"], "1234");
+    assert_eq!(fields[1]["name"], "pin");
+    assert!(fields[1].get("value").is_none());
+
+    let listed: Value =
+        serde_json::from_str(&fa_ok(&env, &["list", "--format", "json", "--reveal"])).unwrap();
+    assert_eq!(listed["credentials"][0]["fields"][1]["value"], "0000");
+}
+
+#[test]
+fn custom_fields_can_be_edited() {
+    let env = TestEnv::new();
+    add_alice(&env);
+
+    fa_ok(
+        &env,
+        &[
+            "edit",
+            "alice",
+            "--field",
+            "account=",
+            "--field",
+            "question=first pet",
+            "--secret-field",
+            "pin=1111",
+            "--note",
+            "",
+        ],
+    );
+    let listed: Value =
+        serde_json::from_str(&fa_ok(&env, &["list", "--format", "json", "--reveal"])).unwrap();
+    let alice = &listed["credentials"][0];
+    assert_eq!(alice["notes"], Value::Null);
+    let fields = alice["fields"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|field| {
+            (
+                field["name"].as_str().unwrap(),
+                field["value"].as_str().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(fields, [("pin", "1111"), ("question", "first pet")]);
+
+    // built in fields can't be shadowed.
+    let output = env.fa(&["edit", "alice", "--field", "password=nope"]);
+    assert!(!output.status.success());
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use common::{env_with_credentials, stderr, stdout, TestEnv};
+use std::fs;
+
+/// the users listed with the filter, in order.
+fn list(env: &TestEnv, filter: &str) -> Vec<String> {
+    let output = env.fa(&[
+        "list",
+        "--format",
+        "plain",
+        "--columns",
+        "user",
+        "--filter",
+        filter,
+    ]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    stdout(&output).lines().map(str::to_string).collect()
+}
+
+#[test]
+fn conditions_are_combined() {
+    let env = env_with_credentials();
+    assert_eq!(
+        list(&env, "site:github.com tag:work -tag:old"),
+        ["alice@gmail.com"]
+    );
+    assert_eq!(list(&env, "user:ci*"), ["ci-bot@corp.com"]);
+    assert_eq!(
+        list(&env, "tag:old OR (site:gitlab AND NOT user=bob@corp.com)"),
+        ["ci-bot@corp.com"]
+    );
+    assert_eq!(list(&env, "tag="), ["carol@example.com"]);
+    assert_eq!(list(&env, "corp"), ["ci-bot@corp.com", "bob@corp.com"]);
+    assert_eq!(
+        list(&env, r#"site~"^git(hub|lab)\.com$" -tag:old"#),
+        ["alice@gmail.com", "bob@corp.com"]
+    );
+}
+
+#[test]
+fn regex_escapes_keep_their_case() {
+    let env = env_with_credentials();
+    let output = env.fa(&["add", "UPPER", "hunter2"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    // '\D' & '\S' aren't '\d' & '\s'.
+    assert_eq!(list(&env, r"user~^\D+$ -user:@"), ["UPPER"]);
+    assert_eq!(
+        list(&env, r"user~^\S+@corp"),
+        ["ci-bot@corp.com", "bob@corp.com"]
+    );
+}
+
+#[test]
+fn the_old_filter_format_still_works() {
+    let env = env_with_credentials();
+    let output = env.fa(&[
+        "search",
+        "",
+        "--filter",
+        "site/gitlab",
+        "--format",
+        "plain",
+        "--columns",
+        "user",
+    ]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "bob@corp.com\n");
+}
+
+#[test]
+fn export_only_writes_the_filtered_credentials() {
+    let env = env_with_credentials();
+    let csv_path = env.home.path().join("export.csv");
+    let output = env.fa(&[
+        "export",
+        "--csv-file",
+        csv_path.to_str().unwrap(),
+        "--filter",
+        "tag:work",
+    ]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        fs::read_to_string(&csv_path).unwrap(),
+        "username,password,url\nalice@gmail.com,hunter2,github.com\nbob@corp.com,hunter2,gitlab.com\n"
+    );
+}
+
+#[test]
+fn parse_errors_point_at_the_bad_token() {
+    let env = env_with_credentials();
+    for (filter, expected) in [
+        ("tag:work foo:bar", "column 10 ('foo:bar')"),
+        ("tag:work)", "column 9 (')')"),
+        ("(tag:work", "column 1 ('(')"),
+        ("tag:work OR", "column 10 ('OR')"),
+        ("site:", "column 1 ('site:')"),
+    ] {
+        let output = env.fa(&["list", "--filter", filter]);
+        assert!(!output.status.success(), "{}", filter);
+        assert!(stderr(&output).contains(expected), "{}", stderr(&output));
+    }
+}
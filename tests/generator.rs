@@ -0,0 +1,37 @@
+mod common;
+
+use common::{fa_ok, stderr, TestEnv};
+
+#[test]
+fn add_generates_a_password() {
+    let env = TestEnv::new();
+    fa_ok(&env, &["add", "alice", "--generate", "--length", "40"]);
+    let password = fa_ok(&env, &["get", "alice"]);
+    assert_eq!(password.trim_end().chars().count(), 40);
+
+    assert_eq!(
+        fa_ok(&env, &["generate", "--length", "12"])
+            .trim_end()
+            .len(),
+        12
+    );
+}
+
+#[test]
+fn generator_options_need_generate() {
+    let env = TestEnv::new();
+    let output = env.fa(&["add", "alice", "--length", "40"]);
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("--generate"),
+        "{}",
+        stderr(&output)
+    );
+
+    // a passed password would silently ignore them too.
+    let output = env.fa(&["add", "alice", "hunter2", "--passphrase"]);
+    assert!(!output.status.success());
+    let output = env.fa(&["add", "alice", "--password-stdin", "--words", "4"]);
+    assert!(!output.status.success());
+    assert!(!env.store_path("main").exists());
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use common::{fa_ok, stderr, stdout, TestEnv};
+
+#[test]
+fn prints_a_single_field() {
+    let env = TestEnv::new();
+    fa_ok(&env, &["add", "alice", "hunter2", "--site", "example.com"]);
+
+    let output = env.fa(&["get", "alice"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "hunter2\n");
+
+    let output = env.fa(&["get", "alice", "--field", "site"]);
+    assert_eq!(stdout(&output), "example.com\n");
+}
+
+#[test]
+fn exits_with_3_without_a_match() {
+    let env = TestEnv::new();
+    fa_ok(&env, &["add", "alice", "hunter2", "--site", "example.com"]);
+
+    let output = env.fa(&["get", "nobody"]);
+    assert_eq!(output.status.code(), Some(3), "{}", stderr(&output));
+    assert!(stdout(&output).is_empty());
+
+    let output = env.fa(&["get", "alice", "--site", "example.org"]);
+    assert_eq!(output.status.code(), Some(3), "{}", stderr(&output));
+}
+
+#[test]
+fn exits_with_4_on_an_ambiguous_match() {
+    let env = TestEnv::new();
+    fa_ok(&env, &["add", "alice", "hunter2", "--site", "example.com"]);
+    fa_ok(
+        &env,
+        &["add", "alice", "swordfish", "--site", "example.org"],
+    );
+
+    let output = env.fa(&["get", "alice"]);
+    assert_eq!(output.status.code(), Some(4), "{}", stderr(&output));
+    assert!(stdout(&output).is_empty());
+
+    // the site tells them apart.
+    let output = env.fa(&["get", "alice", "--site", "example.org"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "swordfish\n");
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use common::{stderr, stdout, TestEnv};
+use serde_json::Value;
+
+fn json(output: &std::process::Output) -> Value {
+    serde_json::from_str(&stdout(output)).unwrap()
+}
+
+#[test]
+fn list_prints_json() {
+    let env = TestEnv::new();
+    assert!(env
+        .fa(&["add", "alice", "hunter2", "--site", "example.com"])
+        .status
+        .success());
+
+    let output = env.fa(&["--format", "json", "list"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let value = json(&output);
+    assert_eq!(value["store"], "main");
+    let credential = &value["credentials"][0];
+    assert_eq!(credential["user"], "alice");
+    assert_eq!(credential["site"], "example.com");
+    assert!(credential.get("password").is_none());
+
+    let output = env.fa(&["list", "--format", "json", "--reveal"]);
+    assert_eq!(json(&output)["credentials"][0]["password"], "hunter2");
+}
+
+#[test]
+fn store_list_and_config_print_json() {
+    let env = TestEnv::new();
+    assert!(env.fa(&["store", "add", "other"]).status.success());
+
+    let output = env.fa(&["--format", "json", "store", "list"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let stores = json(&output)["stores"].as_array().unwrap().clone();
+    let names = stores
+        .iter()
+        .map(|store| store["name"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert!(names.contains(&"other"), "{:?}", names);
+
+    let output = env.fa(&["--format", "json", "config"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        json(&output)["config"]["security"]["gpg_fingerprint"],
+        env.fingerprint.as_str()
+    );
+}
+
+#[test]
+fn errors_are_json_objects() {
+    let env = TestEnv::new();
+    assert!(env.fa(&["add", "alice", "hunter2"]).status.success());
+    let output = env.fa(&["--format", "json", "get", "nobody"]);
+    assert_eq!(output.status.code(), Some(3));
+    let value: Value = serde_json::from_str(stderr(&output).trim()).unwrap();
+    assert_eq!(value["error"]["kind"], "NoMatchingCredential");
+}
+
+#[test]
+fn plain_list_is_tab_separated() {
+    let env = TestEnv::new();
+    assert!(env.fa(&["add", "alice", "hunter2"]).status.success());
+
+    let output = env.fa(&["--format", "plain", "list", "--reveal"]);
+    let fields = stdout(&output)
+        .lines()
+        .next()
+        .unwrap()
+        .split('\t')
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    assert_eq!(fields[1..], ["alice", "hunter2", "", ""]);
+}
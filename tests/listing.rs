@@ -0,0 +1,53 @@
+mod common;
+
+use common::{stderr, stdout, TestEnv};
+
+fn add(env: &TestEnv, user: &str, site: &str) {
+    let output = env.fa(&["add", user, "hunter2", "--site", site]);
+    assert!(output.status.success(), "{}", stderr(&output));
+}
+
+#[test]
+fn table_columns_are_aligned() {
+    let env = TestEnv::new();
+    add(&env, "alice.with.a.long.email@example.com", "a.example.com");
+    add(&env, "bob", "b.example.com");
+
+    let output = env.fa(&["list"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let rows = stdout(&output)
+        .lines()
+        .skip(1)
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    assert_eq!(rows.len(), 3);
+    let separators = |row: &String| {
+        row.match_indices(" | ")
+            .map(|(offset, _)| offset)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(separators(&rows[0]), separators(&rows[1]));
+    assert_eq!(separators(&rows[1]), separators(&rows[2]));
+}
+
+#[test]
+fn columns_can_be_picked_and_sorted() {
+    let env = TestEnv::new();
+    add(&env, "alice", "z.example.com");
+    add(&env, "bob", "a.example.com");
+
+    let output = env.fa(&[
+        "list",
+        "--format",
+        "plain",
+        "--columns",
+        "user,site",
+        "--sort",
+        "site",
+    ]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        "bob\ta.example.com\nalice\tz.example.com\n"
+    );
+}
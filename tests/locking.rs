@@ -0,0 +1,41 @@
+mod common;
+
+use common::{stderr, stdout, TestEnv};
+use std::fs::File;
+
+#[test]
+fn parallel_adds_are_not_lost() {
+    let env = TestEnv::new();
+    let users = (0..8).map(|i| format!("user{}", i)).collect::<Vec<_>>();
+
+    let children = users
+        .iter()
+        .map(|user| env.fa_command(&["add", user, "hunter2"]).spawn().unwrap())
+        .collect::<Vec<_>>();
+    for child in children {
+        let output = child.wait_with_output().unwrap();
+        assert!(output.status.success(), "{}", stderr(&output));
+    }
+
+    let output = env.fa(&["list"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    for user in users.iter() {
+        assert!(stdout(&output).contains(user), "{} is missing", user);
+    }
+}
+
+#[test]
+fn locked_store_times_out() {
+    let env = TestEnv::new();
+    assert!(env.fa(&["add", "alice", "hunter2"]).status.success());
+    env.edit_config("lock_timeout = 10", "lock_timeout = 0");
+
+    let lock_file = File::open(env.store_dir().join(".main.lock")).unwrap();
+    lock_file.lock().unwrap();
+    let output = env.fa(&["add", "bob", "correct-horse"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("in use"), "{}", stderr(&output));
+
+    lock_file.unlock().unwrap();
+    assert!(env.fa(&["add", "bob", "correct-horse"]).status.success());
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::{stderr, stdout, TestEnv};
+use std::fs;
+
+#[test]
+fn opens_stores_written_by_gpg() {
+    let mut env = TestEnv::new();
+    let output = env.fa(&[
+        "add",
+        "meow@example.com",
+        "hunter2",
+        "--site",
+        "example.com",
+    ]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    env.use_native_backend();
+    let output = env.fa(&[
+        "list",
+        "--reveal",
+        "--format",
+        "plain",
+        "--columns",
+        "user,password,site",
+    ]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "meow@example.com\thunter2\texample.com\n");
+}
+
+#[test]
+fn writes_stores_readable_by_gpg() {
+    let mut env = TestEnv::new();
+    let gnupg_home = env.use_native_backend();
+    let output = env.fa(&["add", "meow@example.com", "hunter2"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    // strip the "fa:openpgp" header line, the rest is a plain OpenPGP message.
+    let contents = fs::read(env.store_path("main")).unwrap();
+    assert!(contents.starts_with(b"fa:openpgp\n"));
+    let message = env.home.path().join("message.gpg");
+    fs::write(&message, &contents[b"fa:openpgp\n".len()..]).unwrap();
+
+    let output = common::gpg(&gnupg_home, &["--decrypt", message.to_str().unwrap()]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("hunter2"));
+}
+
+#[test]
+fn rejects_keyring_without_the_configured_key() {
+    let mut env = TestEnv::new();
+    env.use_native_backend();
+    fs::write(env.home.path().join(".config/fa/keyring.asc"), "").unwrap();
+
+    let output = env.fa(&["list"]);
+    assert!(!output.status.success());
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use common::{stderr, stdout, TestEnv};
+use std::{
+    io::Write,
+    process::{Output, Stdio},
+};
+
+/// run fa with the given stdin.
+fn fa_with_stdin(env: &TestEnv, args: &[&str], input: &str) -> Output {
+    let mut child = env
+        .fa_command(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn password(env: &TestEnv) -> String {
+    let output = env.fa(&["get", "alice"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    stdout(&output)
+}
+
+fn warns(output: &Output) -> bool {
+    stderr(output).contains("end up in your shell history")
+}
+
+#[test]
+fn passwords_are_read_from_stdin() {
+    let env = TestEnv::new();
+    let output = fa_with_stdin(&env, &["add", "alice", "--password-stdin"], "hunter2\n");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(!warns(&output), "{}", stderr(&output));
+    assert_eq!(password(&env), "hunter2\n");
+
+    let output = fa_with_stdin(
+        &env,
+        &["edit", "alice", "--password-stdin"],
+        "correct horse\r\n",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(!warns(&output), "{}", stderr(&output));
+    assert_eq!(password(&env), "correct horse\n");
+}
+
+#[test]
+fn password_arguments_are_warned_about() {
+    let env = TestEnv::new();
+    let output = env.fa(&["add", "alice", "hunter2"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(warns(&output), "{}", stderr(&output));
+
+    let output = env.fa(&["edit", "alice", "--password", "correct-horse"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(warns(&output), "{}", stderr(&output));
+    assert_eq!(password(&env), "correct-horse\n");
+
+    // only a single way to pass the password is allowed.
+    let output = env.fa(&["add", "bob", "hunter2", "--password-stdin"]);
+    assert!(!output.status.success());
+    let output = env.fa(&["edit", "alice", "--password", "x", "--password-stdin"]);
+    assert!(!output.status.success());
+}
@@ -0,0 +1,161 @@
+mod common;
+
+use common::{fa_ok, generate_key, gpg, stderr, TestEnv};
+use std::fs;
+use tempfile::TempDir;
+
+/// someone to share the store with, who knows the key of the environment and
+/// has 'fa' set up with the same store directory.
+struct Friend {
+    env: TestEnv,
+}
+
+impl Friend {
+    fn new(env: &TestEnv) -> Self {
+        let gnupg_home = TempDir::new().unwrap();
+        let fingerprint = generate_key(&gnupg_home, "friend <friend@test.invalid>");
+        exchange_keys(&env.gnupg_home, &gnupg_home, &env.fingerprint);
+        exchange_keys(&gnupg_home, &env.gnupg_home, &fingerprint);
+        let friend_env = TestEnv {
+            home: TempDir::new().unwrap(),
+            gnupg_home,
+            fingerprint,
+        };
+        fa_ok(
+            &friend_env,
+            &[
+                "init",
+                "--key-fingerprint",
+                &friend_env.fingerprint,
+                "--store",
+                "main",
+                "--store-path",
+                env.store_dir().to_str().unwrap(),
+            ],
+        );
+        Self { env: friend_env }
+    }
+
+    /// whether the friend can decrypt the 'main' store.
+    fn can_decrypt(&self, env: &TestEnv) -> bool {
+        let contents = fs::read(env.store_path("main")).unwrap();
+        let message = self.env.gnupg_home.path().join("main.gpg");
+        fs::write(&message, &contents[b"fa:openpgp\n".len()..]).unwrap();
+        gpg(
+            &self.env.gnupg_home,
+            &["--decrypt", message.to_str().unwrap()],
+        )
+        .status
+        .success()
+    }
+}
+
+/// import the public key from one keyring into another.
+fn exchange_keys(from: &TempDir, to: &TempDir, fingerprint: &str) {
+    let public_key = to.path().join("exchanged.asc");
+    fs::write(
+        &public_key,
+        gpg(from, &["--armor", "--export", fingerprint]).stdout,
+    )
+    .unwrap();
+    let output = gpg(to, &["--import", public_key.to_str().unwrap()]);
+    assert!(output.status.success(), "{}", stderr(&output));
+}
+
+#[test]
+fn recipients_are_added_and_removed() {
+    let env = TestEnv::new();
+    fa_ok(&env, &["add", "alice", "hunter2"]);
+    let friend = Friend::new(&env);
+    assert!(!friend.can_decrypt(&env));
+
+    fa_ok(
+        &env,
+        &[
+            "store",
+            "recipients",
+            "add",
+            "main",
+            &friend.env.fingerprint,
+        ],
+    );
+    assert!(friend.can_decrypt(&env));
+
+    fa_ok(
+        &env,
+        &[
+            "store",
+            "recipients",
+            "remove",
+            "main",
+            &friend.env.fingerprint,
+        ],
+    );
+    assert!(!friend.can_decrypt(&env));
+    fa_ok(&env, &["list"]);
+}
+
+#[test]
+fn refuses_to_remove_the_own_key() {
+    let env = TestEnv::new();
+    fa_ok(&env, &["add", "alice", "hunter2"]);
+    let friend = Friend::new(&env);
+    fa_ok(
+        &env,
+        &[
+            "store",
+            "recipients",
+            "add",
+            "main",
+            &friend.env.fingerprint,
+        ],
+    );
+
+    let before = fs::read(env.store_path("main")).unwrap();
+    let output = env.fa(&["store", "recipients", "remove", "main", &env.fingerprint]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("own key"), "{}", stderr(&output));
+    assert_eq!(fs::read(env.store_path("main")).unwrap(), before);
+    fa_ok(&env, &["list"]);
+}
+
+#[test]
+fn recipients_can_open_each_others_changes() {
+    let env = TestEnv::new();
+    fa_ok(&env, &["add", "alice", "hunter2"]);
+    let friend = Friend::new(&env);
+    fa_ok(
+        &env,
+        &[
+            "store",
+            "recipients",
+            "add",
+            "main",
+            &friend.env.fingerprint,
+        ],
+    );
+
+    // the friend trusts the existing recipient by adding it again.
+    let output = friend.env.fa(&["list"]);
+    assert!(!output.status.success());
+    fa_ok(
+        &friend.env,
+        &["store", "recipients", "add", "main", &env.fingerprint],
+    );
+    fa_ok(&friend.env, &["add", "bob", "swordfish"]);
+
+    // the store is signed by the friend now.
+    let users = fa_ok(&env, &["list", "--format", "plain", "--columns", "user"]);
+    assert_eq!(users, "alice\nbob\n");
+
+    // once removed, the friend isn't trusted anymore. the key id is enough.
+    let key_id = &friend.env.fingerprint[24..];
+    fa_ok(&friend.env, &["edit", "bob", "--site", "example.com"]);
+    let output = env.fa(&["store", "recipients", "add", "main", key_id]);
+    assert!(stderr(&output).contains("already"), "{}", stderr(&output));
+    fa_ok(&env, &["store", "recipients", "remove", "main", key_id]);
+    fa_ok(&env, &["list"]);
+    assert!(!fs::read_to_string(env.config_path())
+        .unwrap()
+        .contains(&friend.env.fingerprint));
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use common::{fa_ok, generate_key, stderr, TestEnv};
+use std::fs;
+
+/// an environment with a 'main' & a 'work' store and a second key to rekey
+/// to. returns the fingerprint of the second key.
+fn env_with_stores() -> (TestEnv, String) {
+    let env = TestEnv::new();
+    fa_ok(&env, &["add", "alice", "hunter2"]);
+    fa_ok(&env, &["store", "add", "work"]);
+    fa_ok(&env, &["add", "bob", "hunter2", "--store", "work"]);
+    let new_fingerprint = generate_key(&env.gnupg_home, "new key <new@test.invalid>");
+    (env, new_fingerprint)
+}
+
+/// drop the old secret key, so only the new key can open the stores.
+fn forget_old_key(env: &TestEnv) {
+    let output = env.gpg(&["--delete-secret-keys", &env.fingerprint]);
+    assert!(output.status.success(), "{}", stderr(&output));
+}
+
+fn users(env: &TestEnv, store: &str) -> String {
+    fa_ok(
+        env,
+        &[
+            "list",
+            "--store",
+            store,
+            "--format",
+            "plain",
+            "--columns",
+            "user",
+        ],
+    )
+}
+
+#[test]
+fn rekeys_every_store() {
+    let (env, new_fingerprint) = env_with_stores();
+    fa_ok(&env, &["rekey", "--to", &new_fingerprint]);
+    // the new key is the configured one, not just a trusted signer.
+    let config = fs::read_to_string(env.config_path()).unwrap();
+    assert_eq!(config.matches(&new_fingerprint).count(), 1, "{}", config);
+    assert!(config.contains(&format!("gpg_fingerprint = \"{}\"", new_fingerprint)));
+
+    forget_old_key(&env);
+    assert_eq!(users(&env, "main"), "alice\n");
+    assert_eq!(users(&env, "work"), "bob\n");
+}
+
+#[test]
+fn a_failed_rekey_can_be_finished() {
+    let (env, new_fingerprint) = env_with_stores();
+
+    // the backup of 'work' can't be written, so only 'main' is rekeyed.
+    let blocked_backups = env.store_dir().join(".backups/work");
+    fs::remove_dir_all(&blocked_backups).unwrap();
+    fs::write(&blocked_backups, "").unwrap();
+    let output = env.fa(&["rekey", "--to", &new_fingerprint]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("again"), "{}", stderr(&output));
+
+    // the configuration is only switched once every store is rekeyed, but
+    // the rekeyed store and the untouched one both stay readable.
+    assert!(fs::read_to_string(env.config_path())
+        .unwrap()
+        .contains(&env.fingerprint));
+    assert_eq!(users(&env, "main"), "alice\n");
+    assert_eq!(users(&env, "work"), "bob\n");
+
+    fs::remove_file(&blocked_backups).unwrap();
+    fa_ok(&env, &["rekey", "--to", &new_fingerprint]);
+    forget_old_key(&env);
+    assert_eq!(users(&env, "main"), "alice\n");
+    assert_eq!(users(&env, "work"), "bob\n");
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use common::{env_with_credentials, stderr, stdout, TestEnv};
+
+/// the users found by a search, in order.
+fn search(env: &TestEnv, args: &[&str]) -> Vec<String> {
+    let mut command = vec!["search", "--format", "plain", "--columns", "user"];
+    command.extend_from_slice(args);
+    let output = env.fa(&command);
+    assert!(output.status.success(), "{}", stderr(&output));
+    stdout(&output).lines().map(str::to_string).collect()
+}
+
+#[test]
+fn fuzzy_search_finds_fragments_of_any_field() {
+    let env = env_with_credentials();
+    assert_eq!(search(&env, &["gmail"]), ["alice@gmail.com"]);
+    let mut found = search(&env, &["gthb"]);
+    found.sort();
+    assert_eq!(found, ["alice@gmail.com", "ci-bot@corp.com"]);
+    assert_eq!(search(&env, &[""]).len(), 4);
+}
+
+#[test]
+fn fuzzy_search_ranks_the_best_match_first() {
+    let env = env_with_credentials();
+    assert_eq!(search(&env, &["gitlab"])[0], "bob@corp.com");
+}
+
+#[test]
+fn exact_and_regex_search() {
+    let env = env_with_credentials();
+    assert_eq!(search(&env, &["--exact", "gthb"]), Vec::<String>::new());
+    assert_eq!(search(&env, &["--exact", "GITLAB"]), ["bob@corp.com"]);
+    assert_eq!(
+        search(&env, &["--regex", r"\.(org|io)$"]),
+        ["carol@example.com"]
+    );
+
+    let output = env.fa(&["search", "--regex", "("]);
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("regular expression"),
+        "{}",
+        stderr(&output)
+    );
+}
@@ -0,0 +1,185 @@
+//! Stores are only opened if they were signed by the configured key or one of
+//! the 'trusted_signers', with either OpenPGP backend.
+
+mod common;
+
+use common::{generate_key, gpg, stderr, stdout, TestEnv};
+use serde_json::Value;
+use std::fs;
+use tempfile::TempDir;
+
+/// a key outside of the keyring of the environment.
+struct Signer {
+    gnupg_home: TempDir,
+    fingerprint: String,
+}
+
+impl Signer {
+    /// a new key that can encrypt to the key of the environment.
+    fn new(env: &TestEnv) -> Self {
+        let gnupg_home = TempDir::new().unwrap();
+        let fingerprint = generate_key(&gnupg_home, "signer <signer@test.invalid>");
+        let public_key = env.home.path().join("public.asc");
+        fs::write(&public_key, env.gpg(&["--armor", "--export"]).stdout).unwrap();
+        let output = gpg(&gnupg_home, &["--import", public_key.to_str().unwrap()]);
+        assert!(output.status.success(), "{}", stderr(&output));
+        Self {
+            gnupg_home,
+            fingerprint,
+        }
+    }
+
+    /// make the key known to the environment, without trusting it yet.
+    fn import_into(&self, env: &TestEnv) {
+        let public_key = env.home.path().join("signer.asc");
+        let output = gpg(
+            &self.gnupg_home,
+            &["--armor", "--export", &self.fingerprint],
+        );
+        fs::write(&public_key, output.stdout).unwrap();
+        let output = env.gpg(&["--import", public_key.to_str().unwrap()]);
+        assert!(output.status.success(), "{}", stderr(&output));
+    }
+}
+
+/// the decrypted contents of the 'main' store.
+fn plaintext(env: &TestEnv) -> Vec<u8> {
+    let contents = fs::read(env.store_path("main")).unwrap();
+    let encrypted = env.home.path().join("main.gpg");
+    fs::write(&encrypted, &contents[b"fa:openpgp\n".len()..]).unwrap();
+    let output = env.gpg(&["--decrypt", encrypted.to_str().unwrap()]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    output.stdout
+}
+
+/// replace the 'main' store with its contents encrypted to the key of the
+/// environment, signed by the given key or not at all.
+fn rewrite_store(env: &TestEnv, gnupg_home: &TempDir, signer: Option<&str>) {
+    let plaintext_path = env.home.path().join("main.json");
+    fs::write(&plaintext_path, plaintext(env)).unwrap();
+    let encrypted = env.home.path().join("main.gpg");
+    let mut args = vec![
+        "--trust-model",
+        "always",
+        "--recipient",
+        &env.fingerprint,
+        "--output",
+        encrypted.to_str().unwrap(),
+        "--encrypt",
+    ];
+    if let Some(signer) = signer {
+        args.extend(["--local-user", signer, "--sign"]);
+    }
+    args.push(plaintext_path.to_str().unwrap());
+    let output = gpg(gnupg_home, &args);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let mut contents = b"fa:openpgp\n".to_vec();
+    contents.extend(fs::read(&encrypted).unwrap());
+    fs::write(env.store_path("main"), contents).unwrap();
+}
+
+fn env_with_store() -> TestEnv {
+    let env = TestEnv::new();
+    let output = env.fa(&["add", "alice", "hunter2"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    env
+}
+
+fn assert_untrusted(env: &TestEnv) {
+    let output = env.fa(&["list", "--format", "json"]);
+    assert!(!output.status.success());
+    let error: Value = serde_json::from_str(&stderr(&output)).unwrap();
+    assert_eq!(error["error"]["kind"], "UntrustedSignature", "{}", error);
+}
+
+fn assert_opens(env: &TestEnv) {
+    let output = env.fa(&["list", "--format", "plain", "--columns", "user"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "alice\n");
+}
+
+#[test]
+fn refuses_stores_signed_by_unknown_keys() {
+    for native in [false, true] {
+        let mut env = env_with_store();
+        let signer = Signer::new(&env);
+        rewrite_store(&env, &signer.gnupg_home, Some(&signer.fingerprint));
+        if native {
+            env.use_native_backend();
+        }
+        assert_untrusted(&env);
+    }
+}
+
+#[test]
+fn refuses_unsigned_stores() {
+    for native in [false, true] {
+        let mut env = env_with_store();
+        let gnupg_home = TempDir::new().unwrap();
+        let public_key = env.home.path().join("public.asc");
+        fs::write(&public_key, env.gpg(&["--armor", "--export"]).stdout).unwrap();
+        gpg(&gnupg_home, &["--import", public_key.to_str().unwrap()]);
+        rewrite_store(&env, &gnupg_home, None);
+        if native {
+            env.use_native_backend();
+        }
+        assert_untrusted(&env);
+    }
+}
+
+#[test]
+fn opens_stores_signed_by_trusted_signers() {
+    for native in [false, true] {
+        let mut env = env_with_store();
+        let signer = Signer::new(&env);
+        signer.import_into(&env);
+        rewrite_store(&env, &signer.gnupg_home, Some(&signer.fingerprint));
+        if native {
+            env.use_native_backend();
+        }
+
+        // being in the keyring isn't enough.
+        assert_untrusted(&env);
+
+        let backend = match native {
+            true => "backend = \"native\"",
+            false => "backend = \"gpg\"",
+        };
+        env.edit_config(
+            backend,
+            &format!(
+                "{}\ntrusted_signers = [\"{}\"]",
+                backend, signer.fingerprint
+            ),
+        );
+        assert_opens(&env);
+    }
+}
+
+#[test]
+fn configured_keys_are_resolved_to_fingerprints() {
+    for native in [false, true] {
+        let mut env = env_with_store();
+        let signer = Signer::new(&env);
+        signer.import_into(&env);
+        rewrite_store(&env, &signer.gnupg_home, Some(&signer.fingerprint));
+        if native {
+            env.use_native_backend();
+        }
+
+        // the configured key by its user id & the signer by its key id.
+        env.edit_config(
+            &format!("gpg_fingerprint = \"{}\"", env.fingerprint),
+            &format!(
+                "gpg_fingerprint = \"fa@test.invalid\"\ntrusted_signers = [\"{}\"]",
+                &signer.fingerprint[24..]
+            ),
+        );
+        assert_opens(&env);
+        let output = env.fa(&["add", "bob", "hunter2"]);
+        assert!(output.status.success(), "{}", stderr(&output));
+        let output = env.fa(&["list"]);
+        assert!(output.status.success(), "{}", stderr(&output));
+    }
+}
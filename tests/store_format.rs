@@ -0,0 +1,236 @@
+//! The stores in 'tests/fixtures' were written by earlier versions of 'fa'
+//! and are encrypted to the throwaway key in 'tests/fixtures/key.asc'. They
+//! must keep opening as the store format evolves.
+
+mod common;
+
+use common::{fixture_path, stderr, stdout, TestEnv};
+use serde_json::Value;
+use std::fs;
+
+/// install a fixture as the 'main' store.
+fn use_fixture(env: &TestEnv, fixture: &str) {
+    fs::create_dir_all(env.store_dir()).unwrap();
+    fs::copy(fixture_path(fixture), env.store_path("main")).unwrap();
+}
+
+/// decrypt the 'main' store with gpg and parse its contents.
+fn decrypt_store(env: &TestEnv) -> Value {
+    let contents = fs::read(env.store_path("main")).unwrap();
+    let encrypted = contents.strip_prefix(b"fa:openpgp\n".as_slice()).unwrap();
+    let encrypted_path = env.home.path().join("main.gpg");
+    fs::write(&encrypted_path, encrypted).unwrap();
+    let output = env.gpg(&["--decrypt", encrypted_path.to_str().unwrap()]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+fn assert_opens(fixture: &str) {
+    let env = TestEnv::with_fixture_key();
+    use_fixture(&env, fixture);
+
+    let output = env.fa(&["list", "--reveal"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let listed = stdout(&output);
+    for expected in [
+        "alice",
+        "hunter2",
+        "example.com",
+        "work",
+        "bob",
+        "correct-horse",
+    ] {
+        assert!(
+            listed.contains(expected),
+            "{} is missing in {}",
+            expected,
+            listed
+        );
+    }
+}
+
+fn assert_upgrades(fixture: &str) {
+    let env = TestEnv::with_fixture_key();
+    use_fixture(&env, fixture);
+
+    let output = env.fa(&["add", "carol", "swordfish"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let store = decrypt_store(&env);
+    assert_eq!(store["version"], 5);
+    assert_eq!(store["metadata"]["name"], "main");
+    let credentials = store["credentials"].as_array().unwrap();
+    let users = credentials
+        .iter()
+        .map(|credential| credential["user"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(users, ["alice", "bob", "carol"]);
+
+    let mut ids = credentials
+        .iter()
+        .map(|credential| credential["id"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), 3);
+
+    assert!(credentials[0]["tags"]
+        .as_array()
+        .unwrap()
+        .contains(&Value::from("work")));
+    assert_eq!(credentials[1]["tags"], Value::Array(Vec::new()));
+    assert!(credentials
+        .iter()
+        .all(|credential| credential.get("tag").is_none()));
+    assert_eq!(credentials[1]["notes"], Value::Null);
+    assert_eq!(credentials[1]["fields"], Value::Array(Vec::new()));
+    assert!(credentials[2]["created"].is_string());
+    assert!(credentials[2]["password_changed"].is_string());
+}
+
+#[test]
+fn opens_v0_stores_without_header() {
+    assert_opens("v0.fa");
+}
+
+#[test]
+fn opens_v0_stores() {
+    assert_opens("v0-header.fa");
+}
+
+#[test]
+fn opens_v1_stores() {
+    assert_opens("v1.fa");
+}
+
+#[test]
+fn opens_v2_stores() {
+    assert_opens("v2.fa");
+}
+
+#[test]
+fn opens_v3_stores() {
+    assert_opens("v3.fa");
+}
+
+#[test]
+fn opens_v4_stores() {
+    assert_opens("v4.fa");
+}
+
+#[test]
+fn opens_v5_stores() {
+    assert_opens("v5.fa");
+}
+
+#[test]
+fn upgrades_v0_stores_on_save() {
+    assert_upgrades("v0.fa");
+    assert_upgrades("v0-header.fa");
+}
+
+#[test]
+fn upgrades_v1_stores_on_save() {
+    assert_upgrades("v1.fa");
+}
+
+#[test]
+fn upgrades_v2_stores_on_save() {
+    assert_upgrades("v2.fa");
+}
+
+#[test]
+fn upgrades_v3_stores_on_save() {
+    assert_upgrades("v3.fa");
+}
+
+#[test]
+fn upgrades_v4_stores_on_save() {
+    assert_upgrades("v4.fa");
+
+    let env = TestEnv::with_fixture_key();
+    use_fixture(&env, "v4.fa");
+    let output = env.fa(&["add", "carol", "swordfish"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let alice = &decrypt_store(&env)["credentials"][0];
+    assert_eq!(alice["notes"], "recovery codes are in the safe");
+    assert_eq!(alice["fields"][0]["name"], "account");
+    assert_eq!(alice["fields"][1]["secret"], true);
+    assert_eq!(alice["created"], Value::Null);
+    assert_eq!(alice["password_changed"], Value::Null);
+}
+
+#[test]
+fn keeps_v5_stores_on_save() {
+    assert_upgrades("v5.fa");
+
+    let env = TestEnv::with_fixture_key();
+    use_fixture(&env, "v5.fa");
+    let before = decrypt_store(&env);
+    let output = env.fa(&["add", "carol", "swordfish"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let after = decrypt_store(&env);
+    assert_eq!(after["credentials"][0], before["credentials"][0]);
+    assert!(after["credentials"][0]["created"].is_string());
+}
+
+#[test]
+fn keeps_ids_of_upgraded_stores_stable() {
+    let env = TestEnv::with_fixture_key();
+    use_fixture(&env, "v1.fa");
+
+    let ids = |env: &TestEnv| {
+        let output = env.fa(&["list", "--format", "plain", "--columns", "id,user"]);
+        assert!(output.status.success(), "{}", stderr(&output));
+        stdout(&output)
+    };
+    let first = ids(&env);
+    assert_eq!(ids(&env), first);
+
+    // reading an old store doesn't write it.
+    assert_eq!(
+        fs::read(env.store_path("main")).unwrap(),
+        fs::read(fixture_path("v1.fa")).unwrap()
+    );
+    assert!(!env.store_dir().join(".backups").exists());
+
+    // the ids that were shown are the ones that get saved.
+    let output = env.fa(&["add", "carol", "swordfish"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(decrypt_store(&env)["version"], 5);
+    assert!(ids(&env).starts_with(&first));
+}
+
+#[test]
+fn refuses_stores_from_newer_versions() {
+    let env = TestEnv::with_fixture_key();
+    fs::create_dir_all(env.store_dir()).unwrap();
+    let plaintext_path = env.home.path().join("main.json");
+    fs::write(
+        &plaintext_path,
+        r#"{"version":999,"metadata":{},"credentials":[]}"#,
+    )
+    .unwrap();
+    let output = env.gpg(&[
+        "--trust-model",
+        "always",
+        "--local-user",
+        &env.fingerprint,
+        "--recipient",
+        &env.fingerprint,
+        "--output",
+        env.store_path("main").to_str().unwrap(),
+        "--sign",
+        "--encrypt",
+        plaintext_path.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let output = env.fa(&["list"]);
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("newer version"),
+        "{}",
+        stderr(&output)
+    );
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use common::{fa_ok, TestEnv};
+
+/// the users found by a search, in order.
+fn search_users(env: &TestEnv, args: &[&str]) -> Vec<String> {
+    let mut command = vec!["search", "", "--format", "plain", "--columns", "user"];
+    command.extend_from_slice(args);
+    fa_ok(env, &command).lines().map(str::to_string).collect()
+}
+
+#[test]
+fn credentials_have_many_tags() {
+    let env = TestEnv::new();
+    fa_ok(
+        &env,
+        &["add", "alice", "hunter2", "--tag", "work", "-t", "aws"],
+    );
+    fa_ok(&env, &["add", "bob", "hunter2", "--tag", "work,gcp"]);
+    fa_ok(&env, &["add", "carol", "hunter2"]);
+
+    assert_eq!(
+        fa_ok(&env, &["get", "alice", "--field", "tags"]),
+        "aws\nwork\n"
+    );
+    assert_eq!(
+        fa_ok(&env, &["tags", "--format", "plain"]),
+        "aws\t1\ngcp\t1\nwork\t2\n"
+    );
+
+    fa_ok(&env, &["edit", "bob", "--tag", "personal"]);
+    assert_eq!(
+        fa_ok(&env, &["get", "bob", "--field", "tags"]),
+        "personal\n"
+    );
+    fa_ok(&env, &["edit", "bob", "--tag", ""]);
+    let output = env.fa(&["get", "bob", "--field", "tags"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn tags_can_be_added_and_removed() {
+    let env = TestEnv::new();
+    fa_ok(&env, &["add", "alice", "hunter2", "--tag", "work"]);
+
+    fa_ok(&env, &["tag", "add", "alice", "aws"]);
+    assert_eq!(
+        fa_ok(&env, &["get", "alice", "--field", "tags"]),
+        "aws\nwork\n"
+    );
+    fa_ok(&env, &["tag", "remove", "alice", "work"]);
+    assert_eq!(fa_ok(&env, &["get", "alice", "--field", "tags"]), "aws\n");
+}
+
+#[test]
+fn search_matches_any_or_all_tags() {
+    let env = TestEnv::new();
+    fa_ok(&env, &["add", "alice", "hunter2", "--tag", "work,aws"]);
+    fa_ok(&env, &["add", "bob", "hunter2", "--tag", "work"]);
+    fa_ok(&env, &["add", "carol", "hunter2", "--tag", "aws"]);
+
+    assert_eq!(
+        search_users(&env, &["--tag", "work", "--tag", "aws"]),
+        ["alice", "bob", "carol"]
+    );
+    assert_eq!(
+        search_users(&env, &["--tag", "work", "--tag", "aws", "--all-tags"]),
+        ["alice"]
+    );
+    assert_eq!(
+        search_users(&env, &["--filter", "tag:work -tag:aws"]),
+        ["bob"]
+    );
+}
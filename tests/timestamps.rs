@@ -0,0 +1,98 @@
+mod common;
+
+use common::{fa_ok, fixture_path, TestEnv};
+use serde_json::Value;
+use std::fs;
+
+fn show(env: &TestEnv, target: &str) -> Value {
+    serde_json::from_str(&fa_ok(env, &["show", target, "--format", "json"])).unwrap()
+}
+
+#[test]
+fn edits_are_timestamped() {
+    let env = TestEnv::new();
+    fa_ok(&env, &["add", "alice", "hunter2"]);
+    let added = show(&env, "alice");
+    assert!(added["created"].is_string());
+    assert_eq!(added["modified"], added["created"]);
+    assert_eq!(added["password_changed"], added["created"]);
+
+    fa_ok(&env, &["edit", "alice", "--site", "example.com"]);
+    let edited = show(&env, "alice");
+    assert_eq!(edited["created"], added["created"]);
+    assert_ne!(edited["modified"], added["modified"]);
+    assert_eq!(edited["password_changed"], added["password_changed"]);
+
+    fa_ok(&env, &["edit", "alice", "--password", "correct-horse"]);
+    let rotated = show(&env, "alice");
+    assert_ne!(rotated["password_changed"], added["password_changed"]);
+}
+
+#[test]
+fn show_masks_secrets_unless_revealed() {
+    let env = TestEnv::new();
+    fa_ok(
+        &env,
+        &[
+            "add",
+            "alice",
+            "hunter2",
+            "--field",
+            "account=1234",
+            "--secret-field",
+            "pin=0000",
+        ],
+    );
+
+    let shown = fa_ok(&env, &["show", "alice", "--format", "plain"]);
+    assert!(shown.contains("user\talice\n"), "{}", shown);
+    assert!(shown.contains("account\t1234\n"), "{}", shown);
+    assert!(
+        !shown.contains("hunter2") && !shown.contains("0000"),
+        "{}",
+        shown
+    );
+
+    let shown = fa_ok(&env, &["show", "alice", "--format", "plain", "--reveal"]);
+    assert!(shown.contains("password\thunter2\n"), "{}", shown);
+    assert!(shown.contains("pin\t0000\n"), "{}", shown);
+}
+
+#[test]
+fn audit_lists_old_and_unknown_passwords() {
+    let env = TestEnv::with_fixture_key();
+    fs::create_dir_all(env.store_dir()).unwrap();
+    fs::copy(fixture_path("v4.fa"), env.store_path("main")).unwrap();
+    fa_ok(&env, &["add", "carol", "swordfish"]);
+
+    // the passwords of the older store have an unknown age.
+    let audit = |args: &[&str]| {
+        let mut command = vec!["audit", "--format", "plain"];
+        command.extend_from_slice(args);
+        fa_ok(&env, &command)
+    };
+    assert_eq!(
+        audit(&["--older-than", "180d", "--columns", "user"]),
+        "alice\nbob\n"
+    );
+    assert_eq!(
+        audit(&["--older-than", "0s", "--columns", "user"]),
+        "alice\nbob\ncarol\n"
+    );
+
+    // the default columns end with when the password changed.
+    let changed = show(&env, "carol")["password_changed"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let audited = audit(&["--older-than", "0s"]);
+    let carol = audited.lines().last().unwrap();
+    assert!(
+        carol.ends_with(&format!("\t{}", &changed[..10])),
+        "{}",
+        audited
+    );
+
+    let output = env.fa(&["audit", "--older-than", "soon"]);
+    assert!(!output.status.success());
+}